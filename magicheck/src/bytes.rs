@@ -0,0 +1,38 @@
+//! Bounds-checked fixed-width integer reads used by the format parsers.
+
+fn array<const N: usize>(data: &[u8], offset: usize) -> Option<[u8; N]> {
+    data.get(offset..offset.checked_add(N)?)?.try_into().ok()
+}
+
+pub fn u16_le(data: &[u8], offset: usize) -> Option<u16> {
+    array(data, offset).map(u16::from_le_bytes)
+}
+
+pub fn u16_be(data: &[u8], offset: usize) -> Option<u16> {
+    array(data, offset).map(u16::from_be_bytes)
+}
+
+pub fn u32_le(data: &[u8], offset: usize) -> Option<u32> {
+    array(data, offset).map(u32::from_le_bytes)
+}
+
+pub fn u32_be(data: &[u8], offset: usize) -> Option<u32> {
+    array(data, offset).map(u32::from_be_bytes)
+}
+
+pub fn u64_le(data: &[u8], offset: usize) -> Option<u64> {
+    array(data, offset).map(u64::from_le_bytes)
+}
+
+pub fn u64_be(data: &[u8], offset: usize) -> Option<u64> {
+    array(data, offset).map(u64::from_be_bytes)
+}
+
+/// Reads a 16 or 32-bit integer in the byte order given by `big_endian`.
+pub fn u16_endian(data: &[u8], offset: usize, big_endian: bool) -> Option<u16> {
+    if big_endian { u16_be(data, offset) } else { u16_le(data, offset) }
+}
+
+pub fn u32_endian(data: &[u8], offset: usize, big_endian: bool) -> Option<u32> {
+    if big_endian { u32_be(data, offset) } else { u32_le(data, offset) }
+}
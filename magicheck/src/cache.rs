@@ -0,0 +1,251 @@
+//! On-disk cache of detection results, keyed by device, inode, size and mtime.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs::{self, File, Metadata};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::signatures::{self, Signature, TRAILERS};
+
+const FORMAT: &str = "magicheck-cache 3";
+/// Files modified this recently are not cached: a later change within the
+/// same mtime tick would keep the same key and go unnoticed.
+const RACY_WINDOW_SECS: i64 = 2;
+
+/// A cached identification: the matched signature and trailer, warnings
+/// about the file's structure and the leading magic bytes.
+pub struct Detection {
+    pub signature: Option<&'static Signature>,
+    pub trailer: Option<&'static Signature>,
+    pub warnings: Vec<String>,
+    pub magic: Vec<u8>,
+}
+
+struct Entry {
+    size: u64,
+    mtime: i64,
+    mtime_nsec: i64,
+    signature: Option<usize>,
+    trailer: Option<usize>,
+    warnings: Vec<String>,
+    magic: Vec<u8>,
+    path: String,
+}
+
+impl Entry {
+    fn is_current(&self, metadata: &Metadata) -> bool {
+        self.size == metadata.size() && self.mtime == metadata.mtime() && self.mtime_nsec == metadata.mtime_nsec()
+    }
+}
+
+pub struct Cache {
+    path: PathBuf,
+    entries: HashMap<(u64, u64), Entry>,
+    dirty: bool,
+}
+
+/// `$XDG_CACHE_HOME/magicheck/detections`, falling back to `~/.cache`.
+pub fn default_path() -> Option<PathBuf> {
+    let base = match env::var_os("XDG_CACHE_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env::var_os("HOME")?).join(".cache"),
+    };
+    Some(base.join("magicheck").join("detections"))
+}
+
+/// Identifies the signature table, custom definitions included, so results
+/// from a different table are discarded.
+fn fingerprint() -> String {
+    // FNV-1a: stable across builds, unlike the standard library's hasher.
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut feed = |bytes: &[u8]| {
+        for &byte in bytes {
+            hash = (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    };
+    feed(env!("CARGO_PKG_VERSION").as_bytes());
+    for signature in signatures::all() {
+        feed(signature.name.as_bytes());
+        feed(&signature.offset.to_le_bytes());
+        feed(signature.magic);
+        for (offset, magic) in signature.also {
+            feed(&offset.to_le_bytes());
+            feed(magic);
+        }
+    }
+    for trailer in TRAILERS {
+        feed(trailer.name.as_bytes());
+        feed(&trailer.end_window.unwrap_or(0).to_le_bytes());
+        feed(trailer.magic);
+    }
+    format!("{:016x}", hash)
+}
+
+fn parse_hex(text: &str) -> Option<Vec<u8>> {
+    if text == "-" {
+        return Some(Vec::new());
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Parses one entry line; `known` is the number of signatures its index may refer to.
+fn parse_entry(line: &str, known: usize) -> Option<((u64, u64), Entry)> {
+    let mut fields = line.splitn(10, ' ');
+    let mut next = || fields.next();
+    let dev = next()?.parse().ok()?;
+    let ino = next()?.parse().ok()?;
+    let entry = Entry {
+        size: next()?.parse().ok()?,
+        mtime: next()?.parse().ok()?,
+        mtime_nsec: next()?.parse().ok()?,
+        signature: match next()? {
+            "-" => None,
+            index => Some(index.parse().ok().filter(|&i| i < known)?),
+        },
+        trailer: match next()? {
+            "-" => None,
+            index => Some(index.parse().ok().filter(|&i| i < TRAILERS.len())?),
+        },
+        // Hex keeps spaces and newlines in the warnings out of the line format
+        warnings: match String::from_utf8(parse_hex(next()?)?).ok()? {
+            text if text.is_empty() => Vec::new(),
+            text => text.split('\n').map(str::to_string).collect(),
+        },
+        magic: parse_hex(next()?)?,
+        path: next()?.to_string(),
+    };
+    Some(((dev, ino), entry))
+}
+
+impl Cache {
+    /// Loads the cache at `path`; a missing, unreadable or outdated file gives an empty cache.
+    pub fn load(path: PathBuf) -> Cache {
+        let mut cache = Cache { path, entries: HashMap::new(), dirty: false };
+        let Ok(file) = File::open(&cache.path) else {
+            return cache;
+        };
+        let mut lines = BufReader::new(file).lines().map_while(Result::ok);
+        if lines.next() != Some(format!("{} {}", FORMAT, fingerprint())) {
+            cache.dirty = true;
+            return cache;
+        }
+        let known = signatures::all().count();
+        cache.entries = lines.filter_map(|line| parse_entry(&line, known)).collect();
+        cache
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn lookup(&self, metadata: &Metadata) -> Option<Detection> {
+        let entry = self.entries.get(&(metadata.dev(), metadata.ino()))?;
+        if !entry.is_current(metadata) {
+            return None;
+        }
+        Some(Detection {
+            signature: entry.signature.and_then(|index| signatures::all().nth(index)),
+            trailer: entry.trailer.and_then(|index| TRAILERS.get(index)),
+            warnings: entry.warnings.clone(),
+            magic: entry.magic.clone(),
+        })
+    }
+
+    pub fn store(&mut self, file_path: &Path, metadata: &Metadata, detection: &Detection) {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs() as i64);
+        let Some(path) = std::path::absolute(file_path).ok().and_then(|p| p.to_str().map(str::to_string)) else {
+            return;
+        };
+        // Devices and pipes can yield different bytes without their metadata changing.
+        if !metadata.is_file() || metadata.mtime() > now - RACY_WINDOW_SECS || path.contains('\n') {
+            return;
+        }
+        let signature = detection
+            .signature
+            .and_then(|signature| signatures::all().position(|known| std::ptr::eq(known, signature)));
+        let trailer = detection
+            .trailer
+            .and_then(|trailer| TRAILERS.iter().position(|known| std::ptr::eq(known, trailer)));
+        self.entries.insert(
+            (metadata.dev(), metadata.ino()),
+            Entry {
+                size: metadata.size(),
+                mtime: metadata.mtime(),
+                mtime_nsec: metadata.mtime_nsec(),
+                signature,
+                trailer,
+                warnings: detection.warnings.clone(),
+                magic: detection.magic.clone(),
+                path,
+            },
+        );
+        self.dirty = true;
+    }
+
+    /// Drops entries whose file is gone or has changed; returns how many were removed.
+    pub fn prune(&mut self) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|&(dev, ino), entry| {
+            fs::metadata(&entry.path)
+                .is_ok_and(|metadata| metadata.dev() == dev && metadata.ino() == ino && entry.is_current(&metadata))
+        });
+        let removed = before - self.entries.len();
+        self.dirty |= removed > 0;
+        removed
+    }
+
+    pub fn clear(&mut self) -> io::Result<()> {
+        self.entries.clear();
+        self.dirty = false;
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// Writes the cache if it changed, replacing the old file atomically.
+    pub fn save(&self) -> io::Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let temporary = self.path.with_extension(format!("tmp.{}", std::process::id()));
+        let mut out = BufWriter::new(File::create(&temporary)?);
+        writeln!(out, "{} {}", FORMAT, fingerprint())?;
+        for (&(dev, ino), entry) in &self.entries {
+            let index = |index: Option<usize>| index.map_or_else(|| "-".to_string(), |index| index.to_string());
+            let hex = |bytes: &[u8]| match bytes {
+                [] => "-".to_string(),
+                bytes => bytes.iter().map(|b| format!("{:02x}", b)).collect(),
+            };
+            writeln!(
+                out,
+                "{} {} {} {} {} {} {} {} {} {}",
+                dev,
+                ino,
+                entry.size,
+                entry.mtime,
+                entry.mtime_nsec,
+                index(entry.signature),
+                index(entry.trailer),
+                hex(entry.warnings.join("\n").as_bytes()),
+                hex(&entry.magic),
+                entry.path
+            )?;
+        }
+        out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        fs::rename(&temporary, &self.path)
+    }
+}
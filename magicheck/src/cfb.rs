@@ -0,0 +1,179 @@
+use std::borrow::Cow;
+use std::io;
+
+use crate::bytes::{u16_le, u32_le};
+use crate::reader::Source;
+use crate::signatures::Details;
+
+const HEADER_SIZE: usize = 512;
+const DIRECTORY_ENTRY_SIZE: usize = 128;
+/// Sector numbers at or above this value are chain terminators or free markers.
+const MAX_REGULAR_SECTOR: u32 = 0xFFFF_FFFA;
+/// Bounds chain walks so that a cyclic FAT cannot loop forever.
+const MAX_CHAIN_LENGTH: usize = 1 << 16;
+
+const ENTRY_STORAGE: u8 = 1;
+const ENTRY_STREAM: u8 = 2;
+const ENTRY_ROOT: u8 = 5;
+
+/// Root storage CLSIDs that Windows Installer uses in place of readable stream names.
+const MSI_CLSIDS: &[([u8; 16], &str)] = &[
+    (clsid(0x000C1084), "Windows Installer package"),
+    (clsid(0x000C1086), "Windows Installer patch"),
+    (clsid(0x000C1082), "Windows Installer transform"),
+];
+
+/// Builds a `{XXXXXXXX-0000-0000-C000-000000000046}` CLSID in its on-disk byte order.
+const fn clsid(data1: u32) -> [u8; 16] {
+    let d = data1.to_le_bytes();
+    [d[0], d[1], d[2], d[3], 0, 0, 0, 0, 0xC0, 0, 0, 0, 0, 0, 0, 0x46]
+}
+
+/// Streams or storages whose presence identifies the producing application, checked in order.
+const APPLICATIONS: &[(&str, &str)] = &[
+    ("WordDocument", "Word document"),
+    ("Workbook", "Excel workbook"),
+    ("Book", "Excel 5.0/95 workbook"),
+    ("PowerPoint Document", "PowerPoint presentation"),
+    ("__properties_version1.0", "Outlook message"),
+    ("VisioDocument", "Visio drawing"),
+    ("Quill", "Publisher document"),
+    ("EncryptedPackage", "Encrypted Office Open XML document"),
+];
+
+/// Streams and storages that hold a VBA project.
+const VBA_NAMES: &[&str] = &["VBA", "_VBA_PROJECT", "_VBA_PROJECT_CUR", "Macros"];
+
+struct DirectoryEntry {
+    name: String,
+    kind: u8,
+    clsid: [u8; 16],
+}
+
+struct Cfb<'a> {
+    source: &'a Source,
+    sector_size: usize,
+    difat: Vec<u32>,
+}
+
+impl Cfb<'_> {
+    fn sector(&self, sector: u32) -> Option<Cow<'_, [u8]>> {
+        let offset = (sector as u64 + 1) * self.sector_size as u64;
+        self.source.range(offset, self.sector_size).ok().filter(|data| data.len() == self.sector_size)
+    }
+
+    /// Looks up the FAT entry for `sector`, reading only the FAT sector that holds it.
+    fn next(&self, sector: u32) -> Option<u32> {
+        let per_sector = self.sector_size / 4;
+        let fat_sector = *self.difat.get(sector as usize / per_sector)?;
+        let offset = (fat_sector as u64 + 1) * self.sector_size as u64 + (sector as usize % per_sector) as u64 * 4;
+        u32_le(&self.source.range(offset, 4).ok()?, 0)
+    }
+
+    fn chain(&self, start: u32) -> Vec<u32> {
+        let mut sectors = Vec::new();
+        let mut sector = start;
+        while sector < MAX_REGULAR_SECTOR && sectors.len() < MAX_CHAIN_LENGTH {
+            sectors.push(sector);
+            match self.next(sector) {
+                Some(next) => sector = next,
+                None => break,
+            }
+        }
+        sectors
+    }
+
+    fn directory(&self, start: u32) -> Vec<DirectoryEntry> {
+        let mut entries = Vec::new();
+        for sector in self.chain(start) {
+            let Some(data) = self.sector(sector) else { break };
+            for raw in data.chunks_exact(DIRECTORY_ENTRY_SIZE) {
+                let name_length = (u16_le(raw, 0x40).unwrap_or(0) as usize).min(64);
+                let units: Vec<u16> = raw[..name_length.saturating_sub(2)]
+                    .chunks_exact(2)
+                    .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                    .collect();
+                entries.push(DirectoryEntry {
+                    name: String::from_utf16_lossy(&units),
+                    kind: raw[0x42],
+                    clsid: raw[0x50..0x60].try_into().unwrap_or_default(),
+                });
+            }
+        }
+        entries
+    }
+}
+
+fn cfb_details(source: &Source, header: &[u8]) -> Option<Details> {
+    let version = u16_le(header, 0x1A)?;
+    let sector_size = match u16_le(header, 0x1E)? {
+        9 => 512,
+        12 => 4096,
+        _ => return None,
+    };
+    let first_directory = u32_le(header, 0x30)?;
+
+    let mut difat: Vec<u32> = (0..109).filter_map(|i| u32_le(header, 0x4C + i * 4)).take_while(|&s| s < MAX_REGULAR_SECTOR).collect();
+    let mut cfb = Cfb { source, sector_size, difat: Vec::new() };
+    let mut difat_sector = u32_le(header, 0x44)?;
+    // Sectors are counted as well as entries: one listing no entries can still point back to itself
+    let mut walked = 0;
+    while difat_sector < MAX_REGULAR_SECTOR && difat.len() < MAX_CHAIN_LENGTH && walked < MAX_CHAIN_LENGTH {
+        walked += 1;
+        let data = cfb.sector(difat_sector)?;
+        let (entries, next) = data.split_at(sector_size - 4);
+        difat.extend(entries.chunks_exact(4).map(|e| u32::from_le_bytes([e[0], e[1], e[2], e[3]])).take_while(|&s| s < MAX_REGULAR_SECTOR));
+        difat_sector = u32_le(next, 0)?;
+    }
+    cfb.difat = difat;
+
+    let entries = cfb.directory(first_directory);
+    let root = entries.first().filter(|entry| entry.kind == ENTRY_ROOT)?;
+    let live = || entries.iter().filter(|entry| matches!(entry.kind, ENTRY_STORAGE | ENTRY_STREAM));
+    let has = |name: &str| live().any(|entry| entry.name == name);
+
+    let document_type = MSI_CLSIDS
+        .iter()
+        .find(|(clsid, _)| *clsid == root.clsid)
+        .map(|(_, name)| *name)
+        .or_else(|| APPLICATIONS.iter().find(|(stream, _)| has(stream)).map(|(_, name)| *name))
+        .or_else(|| live().any(|entry| entry.name.starts_with("__substg1.0_")).then_some("Outlook message"))
+        .unwrap_or("unknown");
+
+    let vba: Vec<&str> = VBA_NAMES.iter().copied().filter(|name| has(name)).collect();
+    let streams = live().filter(|entry| entry.kind == ENTRY_STREAM).count();
+
+    Some(vec![
+        ("Document Type", document_type.to_string()),
+        ("CFB Version", version.to_string()),
+        ("Streams", streams.to_string()),
+        ("VBA Macros", if vba.is_empty() { "no".to_string() } else { format!("yes ({})", vba.join(", ")) }),
+    ])
+}
+
+/// Identifies the application behind a Compound File Binary container and flags VBA macros.
+pub fn inspect(source: &Source) -> io::Result<Details> {
+    let header = source.range(0, HEADER_SIZE)?;
+    Ok(cfb_details(source, &header)
+        .unwrap_or_else(|| vec![("Parse Error", "truncated or malformed compound file directory".to_string())]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn self_referencing_difat_sector_ends_the_walk() {
+        let mut file = vec![0; HEADER_SIZE * 2];
+        file[..8].copy_from_slice(&[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1]);
+        file[0x1E..0x20].copy_from_slice(&9u16.to_le_bytes());
+        file[0x4C..HEADER_SIZE].fill(0xFF);
+        // DIFAT starts at sector 0, which holds no entries and names itself as the next
+        file[0x44..0x48].copy_from_slice(&0u32.to_le_bytes());
+        file[HEADER_SIZE..HEADER_SIZE * 2 - 4].fill(0xFF);
+        file[HEADER_SIZE * 2 - 4..].copy_from_slice(&0u32.to_le_bytes());
+
+        let source = Source::from_bytes("cfb-difat-loop", &file);
+        assert_eq!(inspect(&source).unwrap()[0].0, "Parse Error");
+    }
+}
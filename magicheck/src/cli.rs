@@ -0,0 +1,393 @@
+use crate::hash::Algorithm;
+
+/// A window of the file to show in the hex dump.
+pub struct DumpRange {
+    pub offset: u64,
+    pub length: usize,
+}
+
+/// How check results are printed.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+/// Command line options for a magicheck run.
+pub struct Options {
+    pub file_paths: Vec<String>,
+    pub dump: Option<DumpRange>,
+    pub expect: Vec<String>,
+    pub only: Vec<String>,
+    pub recursive: bool,
+    pub secrets: bool,
+    pub no_cache: bool,
+    pub definitions: Vec<String>,
+    pub read_devices: bool,
+    pub hash: Vec<Algorithm>,
+    pub format: Format,
+}
+
+/// Maintenance of the detection cache.
+pub enum CacheAction {
+    Info,
+    Prune,
+    Clear,
+}
+
+/// Options for `magicheck watch`.
+pub struct WatchOptions {
+    pub dir: String,
+    pub sort: Option<String>,
+    pub allow: Vec<String>,
+    pub quarantine: Option<String>,
+}
+
+/// Options for `magicheck organize`.
+pub struct OrganizeOptions {
+    pub source: String,
+    pub destination: String,
+    pub move_files: bool,
+    pub fix_extensions: bool,
+    pub dry_run: bool,
+}
+
+/// Options for `magicheck list`.
+pub struct ListOptions {
+    pub search: Option<String>,
+    pub mime: Vec<String>,
+    pub json: bool,
+}
+
+/// Options for `magicheck learn`.
+pub struct LearnOptions {
+    pub files: Vec<String>,
+    pub name: String,
+    pub mime: Option<String>,
+    pub extensions: Vec<String>,
+}
+
+/// What a magicheck invocation asks for.
+pub enum Command {
+    Check(Options),
+    Cache(CacheAction),
+    Watch(WatchOptions),
+    Organize(OrganizeOptions),
+    SelfTest,
+    Signatures { lint: bool },
+    List(ListOptions),
+    Learn(LearnOptions),
+}
+
+const OPTIONS_HELP: &str = "\
+Options:
+  -r, --recursive     Descend into directories given as paths
+  --dump N            Show a hex+ASCII dump of the first N bytes
+  --range OFF:LEN     Show a hex+ASCII dump of LEN bytes starting at OFF
+  --expect TYPES      Exit with status 2 unless every file is one of TYPES
+                      (comma separated extensions or MIME types, e.g. png,jpeg)
+  --only MIMES        Only print the paths of files whose MIME type starts
+                      with one of MIMES (comma separated, e.g. image/,application/pdf)
+  --secrets           Recursively report private keys, key stores and password
+                      databases; exit with status 2 if any is readable or
+                      writable by group or others
+  --no-cache          Neither read nor update the detection cache
+  --definitions FILE  Also load custom signatures from FILE; those in
+                      ~/.config/magicheck/definitions are always loaded
+  --read-devices      Read block devices to identify disk images; otherwise
+                      devices, FIFOs and sockets are only reported by kind
+  --hash ALGORITHMS   Also compute digests of each file while it is read
+                      (comma separated: sha256, md5, blake3)
+  --json              Print one JSON object per file instead of a report
+  --csv               Print one CSV row per file, after a header row
+  --self-test         Check that every signature is detected from a minimal
+                      sample; exit with status 2 if any is unreachable
+
+Commands:
+  cache info          Show where the detection cache lives and its size
+  cache prune         Drop cache entries for files that are gone or changed
+  cache clear         Delete the detection cache
+  list                Show every supported type with its magic bytes, offset,
+                      MIME type and extensions
+    --search TERM     Only types whose name, MIME type or extension mentions TERM
+    --mime MIMES      Only types under one of MIMES (as for --only, e.g. image/*)
+    --json            Print the list as JSON
+  learn FILE...       Derive a signature from samples of one format and print
+                      it as a definitions section
+    --name NAME       Name of the format (required)
+    --mime TYPE       MIME type to record
+    --ext EXTS        Extensions to record (comma separated); by default the
+                      extension all samples share
+  signatures          List signatures in the order they are tried
+    --lint            Report duplicates, prefix conflicts and other entries
+                      that can never match; exit with status 2 if any
+  watch DIR           Classify files as they are written to or moved into DIR,
+                      printing one JSON object per file
+    --sort DEST       Move each file into DEST/<type>, named after the type's
+                      usual extension (or DEST/unknown)
+    --allow TYPES     Flag files that are not one of TYPES (as for --expect)
+    --quarantine DIR  Move files that fail --allow into DIR
+  organize SRC DEST   Copy every file under SRC into DEST/<category> (images,
+                      media, documents, archives, executables or other)
+    --move            Move files instead of copying them
+    --fix-extensions  Give files the usual extension of their detected type
+    --dry-run         Print the plan without touching any file";
+
+pub fn usage(program: &str) -> String {
+    format!(
+        "Usage: {0} [options] <file_path>...\n       {0} cache <info|prune|clear>\n       {0} list [--search TERM] [--mime MIMES] [--json]\n       {0} learn --name NAME [options] <file>...\n       {0} signatures [--lint]\n       {0} watch [options] <dir>\n       {0} organize [options] <src> <dest>\n\n{1}",
+        program, OPTIONS_HELP
+    )
+}
+
+/// Parses a decimal or `0x`-prefixed hexadecimal number.
+pub fn parse_number(value: &str) -> Result<u64, String> {
+    let parsed = match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => value.parse(),
+    };
+    parsed.map_err(|_| format!("Invalid number '{}'", value))
+}
+
+fn parse_length(value: &str) -> Result<usize, String> {
+    let length = parse_number(value)?;
+    usize::try_from(length).map_err(|_| format!("Length '{}' is too large", value))
+}
+
+fn parse_range(value: &str) -> Result<DumpRange, String> {
+    let (offset, length) = value
+        .split_once(':')
+        .ok_or_else(|| format!("Invalid range '{}', expected OFF:LEN", value))?;
+    Ok(DumpRange {
+        offset: parse_number(offset)?,
+        length: parse_length(length)?,
+    })
+}
+
+pub fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn parse_cache_action(args: &[String]) -> Result<CacheAction, String> {
+    match args {
+        [action] => match action.as_str() {
+            "info" => Ok(CacheAction::Info),
+            "prune" => Ok(CacheAction::Prune),
+            "clear" => Ok(CacheAction::Clear),
+            other => Err(format!("Unknown cache command '{}'", other)),
+        },
+        [] => Err("Missing cache command".to_string()),
+        [_, extra, ..] => Err(format!("Unexpected argument '{}'", extra)),
+    }
+}
+
+fn parse_watch_args(args: &[String]) -> Result<WatchOptions, String> {
+    let mut dir = None;
+    let mut sort = None;
+    let mut allow = Vec::new();
+    let mut quarantine = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value_for = |flag: &str| {
+            iter.next()
+                .ok_or_else(|| format!("Missing value for {}", flag))
+        };
+        match arg.as_str() {
+            "--sort" => sort = Some(value_for("--sort")?.to_string()),
+            "--allow" => allow.extend(parse_list(value_for("--allow")?)),
+            "--quarantine" => quarantine = Some(value_for("--quarantine")?.to_string()),
+            flag if flag.starts_with('-') => return Err(format!("Unknown option '{}'", flag)),
+            path if dir.is_none() => dir = Some(path.to_string()),
+            extra => return Err(format!("Unexpected argument '{}'", extra)),
+        }
+    }
+
+    if quarantine.is_some() && allow.is_empty() {
+        return Err("--quarantine requires --allow".to_string());
+    }
+    Ok(WatchOptions {
+        dir: dir.ok_or("Missing directory to watch")?,
+        sort,
+        allow,
+        quarantine,
+    })
+}
+
+fn parse_organize_args(args: &[String]) -> Result<OrganizeOptions, String> {
+    let mut paths = Vec::new();
+    let mut move_files = false;
+    let mut fix_extensions = false;
+    let mut dry_run = false;
+
+    for arg in args {
+        match arg.as_str() {
+            "--move" => move_files = true,
+            "--fix-extensions" => fix_extensions = true,
+            "--dry-run" => dry_run = true,
+            flag if flag.starts_with('-') => return Err(format!("Unknown option '{}'", flag)),
+            path => paths.push(path.to_string()),
+        }
+    }
+
+    let [source, destination]: [String; 2] = paths
+        .try_into()
+        .map_err(|_| "Expected a source and a destination directory".to_string())?;
+    Ok(OrganizeOptions {
+        source,
+        destination,
+        move_files,
+        fix_extensions,
+        dry_run,
+    })
+}
+
+fn parse_list_args(args: &[String]) -> Result<ListOptions, String> {
+    let mut search = None;
+    let mut mime = Vec::new();
+    let mut json = false;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value_for = |flag: &str| {
+            iter.next()
+                .ok_or_else(|| format!("Missing value for {}", flag))
+        };
+        match arg.as_str() {
+            "--search" => search = Some(value_for("--search")?.to_string()),
+            "--mime" => mime.extend(parse_list(value_for("--mime")?)),
+            "--json" => json = true,
+            flag if flag.starts_with('-') => return Err(format!("Unknown option '{}'", flag)),
+            extra => return Err(format!("Unexpected argument '{}'", extra)),
+        }
+    }
+
+    Ok(ListOptions { search, mime, json })
+}
+
+fn parse_learn_args(args: &[String]) -> Result<LearnOptions, String> {
+    let mut files = Vec::new();
+    let mut name = None;
+    let mut mime = None;
+    let mut extensions = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value_for = |flag: &str| {
+            iter.next()
+                .ok_or_else(|| format!("Missing value for {}", flag))
+        };
+        match arg.as_str() {
+            "--name" => name = Some(value_for("--name")?.to_string()),
+            "--mime" => mime = Some(value_for("--mime")?.to_string()),
+            "--ext" => extensions.extend(parse_list(value_for("--ext")?)),
+            flag if flag.starts_with('-') => return Err(format!("Unknown option '{}'", flag)),
+            path => files.push(path.to_string()),
+        }
+    }
+
+    if files.len() < 2 {
+        return Err("Learning a signature needs at least two sample files".to_string());
+    }
+    Ok(LearnOptions {
+        files,
+        name: name.ok_or("Missing --name")?,
+        mime,
+        extensions,
+    })
+}
+
+pub fn parse_args(args: &[String]) -> Result<Command, String> {
+    match args.get(1).map(String::as_str) {
+        Some("cache") => return parse_cache_action(&args[2..]).map(Command::Cache),
+        Some("watch") => return parse_watch_args(&args[2..]).map(Command::Watch),
+        Some("organize") => return parse_organize_args(&args[2..]).map(Command::Organize),
+        Some("learn") => return parse_learn_args(&args[2..]).map(Command::Learn),
+        Some("list") => return parse_list_args(&args[2..]).map(Command::List),
+        Some("signatures") => {
+            return match args.get(2..).unwrap_or_default() {
+                [] => Ok(Command::Signatures { lint: false }),
+                [flag] if flag == "--lint" => Ok(Command::Signatures { lint: true }),
+                [extra, ..] => Err(format!("Unexpected argument '{}'", extra)),
+            };
+        }
+        Some("--self-test") => {
+            return match args.get(2) {
+                Some(extra) => Err(format!("Unexpected argument '{}'", extra)),
+                None => Ok(Command::SelfTest),
+            };
+        }
+        _ => {}
+    }
+
+    let mut file_paths = Vec::new();
+    let mut dump = None;
+    let mut expect = Vec::new();
+    let mut only = Vec::new();
+    let mut recursive = false;
+    let mut secrets = false;
+    let mut no_cache = false;
+    let mut definitions = Vec::new();
+    let mut read_devices = false;
+    let mut hash = Vec::new();
+    let mut format = Format::Text;
+
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        let mut value_for = |flag: &str| {
+            iter.next()
+                .ok_or_else(|| format!("Missing value for {}", flag))
+        };
+        match arg.as_str() {
+            "--dump" => {
+                let length = parse_length(value_for("--dump")?)?;
+                dump = Some(DumpRange { offset: 0, length });
+            }
+            "--range" => dump = Some(parse_range(value_for("--range")?)?),
+            "--expect" => expect.extend(parse_list(value_for("--expect")?)),
+            "--only" => only.extend(parse_list(value_for("--only")?)),
+            "-r" | "--recursive" => recursive = true,
+            "--secrets" => secrets = true,
+            "--no-cache" => no_cache = true,
+            "--definitions" => definitions.push(value_for("--definitions")?.to_string()),
+            "--read-devices" => read_devices = true,
+            "--hash" => {
+                for name in parse_list(value_for("--hash")?) {
+                    let algorithm = Algorithm::parse(&name)
+                        .ok_or_else(|| format!("Unknown hash '{}' (expected sha256, md5 or blake3)", name))?;
+                    if !hash.contains(&algorithm) {
+                        hash.push(algorithm);
+                    }
+                }
+            }
+            "--json" => format = Format::Json,
+            "--csv" => format = Format::Csv,
+            flag if flag.starts_with('-') => return Err(format!("Unknown option '{}'", flag)),
+            path => file_paths.push(path.to_string()),
+        }
+    }
+
+    if file_paths.is_empty() {
+        return Err("Missing file path".to_string());
+    }
+
+    Ok(Command::Check(Options {
+        file_paths,
+        dump,
+        expect,
+        only,
+        recursive,
+        secrets,
+        no_cache,
+        definitions,
+        read_devices,
+        hash,
+        format,
+    }))
+}
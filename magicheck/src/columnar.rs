@@ -0,0 +1,876 @@
+use std::borrow::Cow;
+use std::io::{self, Read};
+
+use flate2::read::DeflateDecoder;
+use serde_json::Value;
+
+use crate::bytes::{u16_le, u32_le, u64_le};
+use crate::reader::Source;
+use crate::signatures::Details;
+
+/// Footers, postscripts and Avro headers larger than this are not decoded.
+const MAX_METADATA_SIZE: usize = 16 * 1024 * 1024;
+/// Only this many columns are listed individually.
+const MAX_LISTED_COLUMNS: usize = 100;
+/// Row counts that need one read per block stop after this many blocks.
+const MAX_COUNTED_BLOCKS: usize = 100_000;
+/// Guards the recursive skipping of nested Thrift structures.
+const MAX_NESTING: usize = 64;
+
+fn read_exact(source: &Source, offset: u64, length: usize) -> Option<Cow<'_, [u8]>> {
+    source.range(offset, length).ok().filter(|data| data.len() == length)
+}
+
+fn push_columns(details: &mut Details, columns: Vec<String>) {
+    details.push(("Columns", columns.len().to_string()));
+    let hidden = columns.len().saturating_sub(MAX_LISTED_COLUMNS);
+    details.extend(columns.into_iter().take(MAX_LISTED_COLUMNS).map(|column| ("Column", column)));
+    if hidden > 0 {
+        details.push(("Column", format!("... and {} more", hidden)));
+    }
+}
+
+fn varint(data: &[u8], pos: &mut usize) -> Option<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *data.get(*pos)?;
+        *pos += 1;
+        value |= ((byte & 0x7F) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+fn zigzag(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+/// A reader for the Thrift compact protocol used by Parquet footers.
+struct Compact<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Compact<'a> {
+    fn byte(&mut self) -> Option<u8> {
+        let byte = *self.data.get(self.pos)?;
+        self.pos += 1;
+        Some(byte)
+    }
+
+    fn int(&mut self) -> Option<i64> {
+        varint(self.data, &mut self.pos).map(zigzag)
+    }
+
+    fn binary(&mut self) -> Option<&'a [u8]> {
+        let length = varint(self.data, &mut self.pos)? as usize;
+        let value = self.data.get(self.pos..self.pos.checked_add(length)?)?;
+        self.pos += length;
+        Some(value)
+    }
+
+    /// Reads the next field header of a struct as (id, type), or `None` at its end.
+    fn field(&mut self, last_id: &mut i16) -> Option<Option<(i16, u8)>> {
+        let header = self.byte()?;
+        if header == 0 {
+            return Some(None);
+        }
+        let delta = (header >> 4) as i16;
+        *last_id = if delta == 0 { self.int()? as i16 } else { last_id.wrapping_add(delta) };
+        Some(Some((*last_id, header & 0x0F)))
+    }
+
+    fn list(&mut self) -> Option<(usize, u8)> {
+        let header = self.byte()?;
+        let size = match header >> 4 {
+            15 => varint(self.data, &mut self.pos)? as usize,
+            size => size as usize,
+        };
+        Some((size, header & 0x0F))
+    }
+
+    /// Skips a value of `kind`; `element` marks list members, whose booleans take a byte.
+    fn skip(&mut self, kind: u8, element: bool, depth: usize) -> Option<()> {
+        if depth > MAX_NESTING {
+            return None;
+        }
+        match kind {
+            1 | 2 if element => self.pos += 1,
+            1 | 2 => {}
+            3 => self.pos += 1,
+            4..=6 => {
+                varint(self.data, &mut self.pos)?;
+            }
+            7 => self.pos += 8,
+            8 => {
+                self.binary()?;
+            }
+            9 | 10 => {
+                let (size, element_kind) = self.list()?;
+                for _ in 0..size {
+                    self.skip(element_kind, true, depth + 1)?;
+                }
+            }
+            11 => {
+                let size = varint(self.data, &mut self.pos)? as usize;
+                if size > 0 {
+                    let kinds = self.byte()?;
+                    for _ in 0..size {
+                        self.skip(kinds >> 4, true, depth + 1)?;
+                        self.skip(kinds & 0x0F, true, depth + 1)?;
+                    }
+                }
+            }
+            12 => {
+                let mut last_id = 0;
+                while let Some((_, field_kind)) = self.field(&mut last_id)? {
+                    self.skip(field_kind, false, depth + 1)?;
+                }
+            }
+            _ => return None,
+        }
+        (self.pos <= self.data.len()).then_some(())
+    }
+}
+
+/// The parts of a Parquet SchemaElement needed to describe a column.
+struct SchemaElement {
+    name: String,
+    physical_type: Option<i64>,
+    logical_type: Option<i16>,
+    converted_type: Option<i64>,
+    children: usize,
+}
+
+fn parquet_schema_element(reader: &mut Compact) -> Option<SchemaElement> {
+    let mut element = SchemaElement { name: String::new(), physical_type: None, logical_type: None, converted_type: None, children: 0 };
+    let mut last_id = 0;
+    while let Some((id, kind)) = reader.field(&mut last_id)? {
+        match (id, kind) {
+            (1, 5) => element.physical_type = Some(reader.int()?),
+            (4, 8) => element.name = String::from_utf8_lossy(reader.binary()?).into_owned(),
+            (5, 5) => element.children = reader.int()?.max(0) as usize,
+            (6, 5) => element.converted_type = Some(reader.int()?),
+            (10, 12) => {
+                // LogicalType is a union: the id of its single set field names the type.
+                let mut union_id = 0;
+                if let Some((logical, logical_kind)) = reader.field(&mut union_id)? {
+                    element.logical_type = Some(logical);
+                    reader.skip(logical_kind, false, 0)?;
+                    let mut end_id = 0;
+                    while let Some((_, extra_kind)) = reader.field(&mut end_id)? {
+                        reader.skip(extra_kind, false, 0)?;
+                    }
+                }
+            }
+            _ => reader.skip(kind, false, 0)?,
+        }
+    }
+    Some(element)
+}
+
+fn parquet_type(element: &SchemaElement) -> String {
+    let physical = match element.physical_type {
+        Some(0) => "BOOLEAN",
+        Some(1) => "INT32",
+        Some(2) => "INT64",
+        Some(3) => "INT96",
+        Some(4) => "FLOAT",
+        Some(5) => "DOUBLE",
+        Some(6) => "BYTE_ARRAY",
+        Some(7) => "FIXED_LEN_BYTE_ARRAY",
+        _ => "group",
+    };
+    let logical = match element.logical_type {
+        Some(1) => Some("STRING"),
+        Some(2) => Some("MAP"),
+        Some(3) => Some("LIST"),
+        Some(4) => Some("ENUM"),
+        Some(5) => Some("DECIMAL"),
+        Some(6) => Some("DATE"),
+        Some(7) => Some("TIME"),
+        Some(8) => Some("TIMESTAMP"),
+        Some(10) => Some("INTEGER"),
+        Some(12) => Some("JSON"),
+        Some(13) => Some("BSON"),
+        Some(14) => Some("UUID"),
+        Some(15) => Some("FLOAT16"),
+        _ => match element.converted_type {
+            Some(0) => Some("UTF8"),
+            Some(1) => Some("MAP"),
+            Some(3) => Some("LIST"),
+            Some(4) => Some("ENUM"),
+            Some(5) => Some("DECIMAL"),
+            Some(6) => Some("DATE"),
+            Some(9) | Some(10) => Some("TIMESTAMP"),
+            Some(19) => Some("JSON"),
+            _ => None,
+        },
+    };
+    match logical {
+        Some(logical) => format!("{} ({})", physical, logical),
+        None => physical.to_string(),
+    }
+}
+
+/// Flattens the depth-first schema list into dotted leaf column paths.
+fn parquet_columns(elements: &[SchemaElement], index: usize, prefix: &str, columns: &mut Vec<String>, depth: usize) -> usize {
+    let mut next = index + 1;
+    let Some(element) = elements.get(index) else {
+        return next;
+    };
+    for _ in 0..element.children {
+        let Some(child) = elements.get(next) else {
+            break;
+        };
+        let path = if prefix.is_empty() { child.name.clone() } else { format!("{}.{}", prefix, child.name) };
+        if child.children == 0 || depth > MAX_NESTING {
+            columns.push(format!("{}: {}", path, parquet_type(child)));
+            next += 1;
+        } else {
+            next = parquet_columns(elements, next, &path, columns, depth + 1);
+        }
+    }
+    next
+}
+
+fn parquet_details(source: &Source, file_size: u64) -> Option<Details> {
+    let trailer = read_exact(source, file_size.checked_sub(8)?, 8)?;
+    if &trailer[4..] != b"PAR1" {
+        return None;
+    }
+    let footer_length = (u32_le(&trailer, 0)? as usize).min(MAX_METADATA_SIZE);
+    let footer = read_exact(source, file_size.checked_sub(8 + footer_length as u64)?, footer_length)?;
+
+    let mut reader = Compact { data: &footer, pos: 0 };
+    let (mut version, mut rows, mut row_groups, mut created_by) = (None, None, None, None);
+    let mut elements = Vec::new();
+    let mut last_id = 0;
+    while let Some((id, kind)) = reader.field(&mut last_id)? {
+        match (id, kind) {
+            (1, 5) => version = Some(reader.int()?),
+            (2, 9) => {
+                let (count, _) = reader.list()?;
+                for _ in 0..count {
+                    elements.push(parquet_schema_element(&mut reader)?);
+                }
+            }
+            (3, 6) => rows = Some(reader.int()?),
+            (4, 9) => {
+                let (count, element_kind) = reader.list()?;
+                for _ in 0..count {
+                    reader.skip(element_kind, true, 0)?;
+                }
+                row_groups = Some(count);
+            }
+            (6, 8) => created_by = Some(String::from_utf8_lossy(reader.binary()?).into_owned()),
+            _ => reader.skip(kind, false, 0)?,
+        }
+    }
+
+    let mut details = vec![("Format", "Parquet".to_string())];
+    if let Some(version) = version {
+        details.push(("Format Version", version.to_string()));
+    }
+    if let Some(created_by) = created_by {
+        details.push(("Created By", created_by));
+    }
+    if let Some(rows) = rows {
+        details.push(("Rows", rows.to_string()));
+    }
+    if let Some(row_groups) = row_groups {
+        details.push(("Row Groups", row_groups.to_string()));
+    }
+    let mut columns = Vec::new();
+    parquet_columns(&elements, 0, "", &mut columns, 0);
+    push_columns(&mut details, columns);
+    Some(details)
+}
+
+/// Iterates over the fields of a protobuf message as (number, wire type, integer value, bytes);
+/// length-delimited fields carry their length as the integer value.
+fn protobuf_fields(data: &[u8]) -> impl Iterator<Item = (u64, u64, u64, &[u8])> {
+    let mut pos = 0;
+    std::iter::from_fn(move || {
+        let key = varint(data, &mut pos)?;
+        let (field, wire) = (key >> 3, key & 0x07);
+        match wire {
+            0 => Some((field, wire, varint(data, &mut pos)?, &data[..0])),
+            1 => {
+                let value = u64_le(data, pos)?;
+                pos += 8;
+                Some((field, wire, value, &data[..0]))
+            }
+            2 => {
+                let length = varint(data, &mut pos)? as usize;
+                let value = data.get(pos..pos.checked_add(length)?)?;
+                pos += length;
+                Some((field, wire, length as u64, value))
+            }
+            5 => {
+                let value = u32_le(data, pos)? as u64;
+                pos += 4;
+                Some((field, wire, value, &data[..0]))
+            }
+            _ => None,
+        }
+    })
+}
+
+/// Collects a repeated varint field that may be packed or unpacked.
+fn protobuf_varints(data: &[u8], wanted: u64) -> Vec<u64> {
+    let mut values = Vec::new();
+    let mut packed = Vec::new();
+    for (field, wire, value, bytes) in protobuf_fields(data) {
+        match (field == wanted, wire) {
+            (true, 2) => packed.push(bytes),
+            (true, _) => values.push(value),
+            _ => {}
+        }
+    }
+    for bytes in packed {
+        let mut pos = 0;
+        while let Some(value) = varint(bytes, &mut pos) {
+            values.push(value);
+        }
+    }
+    values
+}
+
+/// Undoes ORC's chunked zlib framing: each chunk has a 3-byte length header
+/// whose low bit marks chunks that were stored uncompressed. Output stops at
+/// `MAX_METADATA_SIZE` in total, however many chunks there are.
+fn orc_inflate(data: &[u8]) -> Option<Vec<u8>> {
+    let mut output = Vec::new();
+    let mut pos = 0;
+    while pos < data.len() && output.len() < MAX_METADATA_SIZE {
+        let header = *data.get(pos)? as usize | (*data.get(pos + 1)? as usize) << 8 | (*data.get(pos + 2)? as usize) << 16;
+        let chunk = data.get(pos + 3..pos + 3 + (header >> 1))?;
+        let room = MAX_METADATA_SIZE - output.len();
+        if header & 1 == 1 {
+            output.extend_from_slice(&chunk[..chunk.len().min(room)]);
+        } else {
+            DeflateDecoder::new(chunk).take(room as u64).read_to_end(&mut output).ok()?;
+        }
+        pos += 3 + (header >> 1);
+    }
+    Some(output)
+}
+
+fn orc_kind(kind: u64) -> String {
+    const KINDS: &[&str] = &[
+        "boolean", "tinyint", "smallint", "int", "bigint", "float", "double", "string", "binary", "timestamp",
+        "array", "map", "struct", "uniontype", "decimal", "date", "varchar", "char", "timestamp with local time zone",
+    ];
+    KINDS.get(kind as usize).map_or_else(|| format!("unknown ({})", kind), |name| name.to_string())
+}
+
+fn orc_details(source: &Source, file_size: u64) -> Option<Details> {
+    let postscript_length = *read_exact(source, file_size.checked_sub(1)?, 1)?.first()? as u64;
+    let postscript = read_exact(source, file_size.checked_sub(1 + postscript_length)?, postscript_length as usize)?;
+    let (mut footer_length, mut compression) = (0, 0);
+    for (field, _, value, _) in protobuf_fields(&postscript) {
+        match field {
+            1 => footer_length = value,
+            2 => compression = value,
+            _ => {}
+        }
+    }
+    let version: Vec<String> = protobuf_varints(&postscript, 4).iter().map(|v| v.to_string()).collect();
+    let compression_name = match compression {
+        0 => "none",
+        1 => "zlib",
+        2 => "snappy",
+        3 => "lzo",
+        4 => "lz4",
+        5 => "zstd",
+        _ => "unknown",
+    };
+
+    let mut details = vec![("Format", "ORC".to_string())];
+    if !version.is_empty() {
+        details.push(("Format Version", version.join(".")));
+    }
+    details.push(("Compression", compression_name.to_string()));
+
+    let footer_length = (footer_length as usize).min(MAX_METADATA_SIZE);
+    let footer_offset = file_size.checked_sub(1 + postscript_length + footer_length as u64)?;
+    let raw_footer = read_exact(source, footer_offset, footer_length)?;
+    let footer = match compression {
+        0 => raw_footer,
+        1 => Cow::Owned(orc_inflate(&raw_footer)?),
+        // Other codecs need decoders we do not carry; the postscript is all we can report.
+        _ => return Some(details),
+    };
+
+    let mut types = Vec::new();
+    let mut stripes = 0;
+    for (field, _, value, bytes) in protobuf_fields(&footer) {
+        match field {
+            3 => stripes += 1,
+            4 => types.push(bytes),
+            6 => details.push(("Rows", value.to_string())),
+            _ => {}
+        }
+    }
+    details.push(("Stripes", stripes.to_string()));
+
+    let type_kind = |index: u64| {
+        types.get(index as usize).map_or_else(String::new, |data| {
+            orc_kind(protobuf_fields(data).find(|(field, _, _, _)| *field == 1).map_or(0, |(_, _, kind, _)| kind))
+        })
+    };
+    if let Some(root) = types.first() {
+        let subtypes = protobuf_varints(root, 2);
+        let names = protobuf_fields(root).filter(|(field, _, _, _)| *field == 3).map(|(_, _, _, name)| String::from_utf8_lossy(name).into_owned());
+        let columns = names.zip(subtypes).map(|(name, subtype)| format!("{}: {}", name, type_kind(subtype))).collect();
+        push_columns(&mut details, columns);
+    }
+    Some(details)
+}
+
+fn avro_long(data: &[u8], pos: &mut usize) -> Option<i64> {
+    varint(data, pos).map(zigzag)
+}
+
+fn avro_bytes<'a>(data: &'a [u8], pos: &mut usize) -> Option<&'a [u8]> {
+    let length = usize::try_from(avro_long(data, pos)?).ok()?;
+    let value = data.get(*pos..pos.checked_add(length)?)?;
+    *pos += length;
+    Some(value)
+}
+
+fn avro_type(schema: &Value) -> String {
+    match schema {
+        Value::String(name) => name.clone(),
+        Value::Array(branches) => branches.iter().map(avro_type).collect::<Vec<_>>().join(" | "),
+        Value::Object(object) => {
+            let kind = object.get("type").map_or_else(String::new, avro_type);
+            if let Some(logical) = object.get("logicalType").and_then(Value::as_str) {
+                return format!("{} ({})", kind, logical);
+            }
+            match kind.as_str() {
+                "array" => format!("array<{}>", object.get("items").map_or_else(String::new, avro_type)),
+                "map" => format!("map<{}>", object.get("values").map_or_else(String::new, avro_type)),
+                "record" | "enum" | "fixed" => {
+                    format!("{} {}", kind, object.get("name").and_then(Value::as_str).unwrap_or("?"))
+                }
+                _ => kind,
+            }
+        }
+        other => other.to_string(),
+    }
+}
+
+fn avro_details(source: &Source, file_size: u64) -> Option<Details> {
+    let head = source.range(0, MAX_METADATA_SIZE).ok()?;
+    let mut pos = 4;
+    let (mut schema, mut codec) = (None, "null".to_string());
+    loop {
+        let mut count = avro_long(&head, &mut pos)?;
+        if count == 0 {
+            break;
+        }
+        if count < 0 {
+            count = count.checked_neg()?;
+            avro_long(&head, &mut pos)?;
+        }
+        for _ in 0..count {
+            let key = avro_bytes(&head, &mut pos)?;
+            let value = avro_bytes(&head, &mut pos)?;
+            match key {
+                b"avro.schema" => schema = serde_json::from_slice::<Value>(value).ok(),
+                b"avro.codec" => codec = String::from_utf8_lossy(value).into_owned(),
+                _ => {}
+            }
+        }
+    }
+    let data_start = pos as u64 + 16;
+
+    let mut details = vec![("Format", "Avro".to_string()), ("Codec", codec)];
+
+    // Each block header carries its object count and byte size, so rows can be summed by seeking.
+    let (mut rows, mut blocks, mut offset) = (0i64, 0, data_start);
+    while offset < file_size && blocks < MAX_COUNTED_BLOCKS {
+        let header = source.range(offset, 20).ok()?;
+        let mut header_pos = 0;
+        let (Some(objects), Some(size)) = (avro_long(&header, &mut header_pos), avro_long(&header, &mut header_pos)) else {
+            break;
+        };
+        rows = rows.saturating_add(objects);
+        blocks += 1;
+        offset = offset.saturating_add(header_pos as u64 + size.max(0) as u64 + 16);
+    }
+    let mut rows = rows.to_string();
+    if offset < file_size {
+        rows.push_str(" (scan incomplete)");
+    }
+    details.push(("Rows", rows));
+    details.push(("Blocks", blocks.to_string()));
+
+    if let Some(schema) = schema {
+        if let Some(name) = schema.get("name").and_then(Value::as_str) {
+            details.push(("Schema", name.to_string()));
+        }
+        let fields = schema.get("fields").and_then(Value::as_array).cloned().unwrap_or_default();
+        let columns = fields
+            .iter()
+            .map(|field| {
+                let name = field.get("name").and_then(Value::as_str).unwrap_or("?");
+                format!("{}: {}", name, field.get("type").map_or_else(String::new, avro_type))
+            })
+            .collect();
+        push_columns(&mut details, columns);
+    }
+    Some(details)
+}
+
+/// A FlatBuffers table, as used by Arrow IPC footers and messages.
+#[derive(Clone, Copy)]
+struct Table<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Table<'a> {
+    fn root(data: &'a [u8]) -> Option<Self> {
+        Some(Table { data, pos: u32_le(data, 0)? as usize })
+    }
+
+    /// Absolute position of field `index`, or `None` when it is absent.
+    fn field(&self, index: usize) -> Option<usize> {
+        let vtable = self.pos.checked_add_signed(-(u32_le(self.data, self.pos)? as i32 as isize))?;
+        let slot = 4 + 2 * index;
+        if slot >= u16_le(self.data, vtable)? as usize {
+            return None;
+        }
+        match u16_le(self.data, vtable + slot)? {
+            0 => None,
+            offset => Some(self.pos + offset as usize),
+        }
+    }
+
+    fn indirect(&self, position: usize) -> Option<usize> {
+        position.checked_add(u32_le(self.data, position)? as usize)
+    }
+
+    fn u8(&self, index: usize) -> Option<u8> {
+        self.field(index).and_then(|pos| self.data.get(pos).copied())
+    }
+
+    fn u16(&self, index: usize) -> Option<u16> {
+        u16_le(self.data, self.field(index)?)
+    }
+
+    fn i32(&self, index: usize) -> Option<i32> {
+        u32_le(self.data, self.field(index)?).map(|value| value as i32)
+    }
+
+    fn i64(&self, index: usize) -> Option<i64> {
+        u64_le(self.data, self.field(index)?).map(|value| value as i64)
+    }
+
+    fn table(&self, index: usize) -> Option<Table<'a>> {
+        Some(Table { data: self.data, pos: self.indirect(self.field(index)?)? })
+    }
+
+    /// Start and length of a vector field.
+    fn vector(&self, index: usize) -> Option<(usize, usize)> {
+        let start = self.indirect(self.field(index)?)?;
+        Some((start + 4, u32_le(self.data, start)? as usize))
+    }
+
+    fn tables(&self, index: usize) -> Vec<Table<'a>> {
+        let Some((start, length)) = self.vector(index) else {
+            return Vec::new();
+        };
+        // The length is untrusted; no more offsets than the data can hold are looked at
+        let length = length.min(self.data.len().saturating_sub(start) / 4);
+        (0..length)
+            .filter_map(|i| Some(Table { data: self.data, pos: self.indirect(start + i * 4)? }))
+            .collect()
+    }
+
+    fn string(&self, index: usize) -> Option<String> {
+        let (start, length) = self.vector(index)?;
+        Some(String::from_utf8_lossy(self.data.get(start..start.checked_add(length)?)?).into_owned())
+    }
+}
+
+fn arrow_type(field: &Table, depth: usize) -> String {
+    let parameters = field.table(3);
+    let children = || {
+        if depth > MAX_NESTING {
+            return String::new();
+        }
+        field.tables(5).iter().map(|child| arrow_type(child, depth + 1)).collect::<Vec<_>>().join(", ")
+    };
+    match field.u8(2).unwrap_or(0) {
+        1 => "null".to_string(),
+        2 => {
+            let bits = parameters.and_then(|p| p.i32(0)).unwrap_or(0);
+            let signed = parameters.and_then(|p| p.u8(1)).unwrap_or(0) != 0;
+            format!("{}int{}", if signed { "" } else { "u" }, bits)
+        }
+        3 => match parameters.and_then(|p| p.u16(0)).unwrap_or(0) {
+            0 => "float16".to_string(),
+            1 => "float32".to_string(),
+            _ => "float64".to_string(),
+        },
+        4 => "binary".to_string(),
+        5 => "utf8".to_string(),
+        6 => "bool".to_string(),
+        7 => "decimal".to_string(),
+        8 => "date".to_string(),
+        9 => "time".to_string(),
+        10 => "timestamp".to_string(),
+        11 => "interval".to_string(),
+        12 => format!("list<{}>", children()),
+        13 => format!("struct<{}>", children()),
+        14 => "union".to_string(),
+        15 => "fixed_size_binary".to_string(),
+        16 => format!("fixed_size_list<{}>", children()),
+        17 => format!("map<{}>", children()),
+        18 => "duration".to_string(),
+        19 => "large_binary".to_string(),
+        20 => "large_utf8".to_string(),
+        21 => format!("large_list<{}>", children()),
+        other => format!("unknown ({})", other),
+    }
+}
+
+fn arrow_details(source: &Source, file_size: u64) -> Option<Details> {
+    let trailer = read_exact(source, file_size.checked_sub(10)?, 10)?;
+    if &trailer[4..] != b"ARROW1" {
+        return None;
+    }
+    let footer_length = (u32_le(&trailer, 0)? as usize).min(MAX_METADATA_SIZE);
+    let footer_data = read_exact(source, file_size.checked_sub(10 + footer_length as u64)?, footer_length)?;
+    let footer = Table::root(&footer_data)?;
+
+    let mut details = vec![("Format", "Arrow IPC".to_string())];
+    if let Some(version) = footer.u16(0) {
+        details.push(("Format Version", format!("V{}", u32::from(version) + 1)));
+    }
+
+    // Blocks are 24-byte structs: offset, metadata length (padded) and body length.
+    let (start, count) = footer.vector(3).unwrap_or((0, 0));
+    let count = count.min(footer_data.len().saturating_sub(start) / 24);
+    let mut rows = 0i64;
+    for i in 0..count.min(MAX_COUNTED_BLOCKS) {
+        let block = footer_data.get(start + i * 24..start + i * 24 + 24)?;
+        let (offset, length) = (u64_le(block, 0)?, u32_le(block, 8)? as usize);
+        let message = read_exact(source, offset, length)?;
+        // Messages are prefixed by a 0xFFFFFFFF continuation marker and their length.
+        let skip = if u32_le(&message, 0)? == 0xFFFF_FFFF { 8 } else { 4 };
+        let message = Table::root(message.get(skip..)?)?;
+        rows = rows.saturating_add(message.table(2).and_then(|batch| batch.i64(0)).unwrap_or(0));
+    }
+    details.push(("Rows", rows.to_string()));
+    details.push(("Record Batches", count.to_string()));
+
+    if let Some(schema) = footer.table(1) {
+        let columns = schema
+            .tables(1)
+            .iter()
+            .map(|field| format!("{}: {}", field.string(0).unwrap_or_default(), arrow_type(field, 0)))
+            .collect();
+        push_columns(&mut details, columns);
+    }
+    Some(details)
+}
+
+/// Reports format version, schema columns and row counts of columnar data files.
+pub fn inspect(source: &Source) -> io::Result<Details> {
+    let head = source.range(0, 8)?;
+    let file_size = source.size();
+    let details = if head.starts_with(b"PAR1") {
+        parquet_details(source, file_size)
+    } else if head.starts_with(b"ORC") {
+        orc_details(source, file_size)
+    } else if head.starts_with(b"Obj\x01") {
+        avro_details(source, file_size)
+    } else if head.starts_with(b"ARROW1") {
+        arrow_details(source, file_size)
+    } else {
+        None
+    };
+    Ok(details.unwrap_or_else(|| vec![("Parse Error", "truncated or malformed file metadata".to_string())]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inspect_bytes(name: &str, data: &[u8]) -> Details {
+        inspect(&Source::from_bytes(name, data)).unwrap()
+    }
+
+    fn expected(pairs: &[(&'static str, &str)]) -> Details {
+        pairs.iter().map(|&(key, value)| (key, value.to_string())).collect()
+    }
+
+    /// A Parquet file of 10 rows with an INT64 and a UTF8 column.
+    fn parquet_file() -> Vec<u8> {
+        let mut footer = vec![0x15, 0x02, 0x19, 0x3C];
+        footer.extend(b"\x48\x06schema\x15\x04\x00");
+        footer.extend(b"\x15\x04\x38\x02id\x00");
+        footer.extend(b"\x15\x0C\x38\x04name\x25\x00\x00");
+        footer.extend([0x16, 0x14, 0x19, 0x0C]);
+        footer.extend(b"\x28\x04test\x00");
+        [&b"PAR1"[..], &footer, &(footer.len() as u32).to_le_bytes(), b"PAR1"].concat()
+    }
+
+    /// An uncompressed ORC file of 5 rows in one stripe, with a bigint and a string column.
+    fn orc_file() -> Vec<u8> {
+        let mut footer = vec![0x1A, 0x00];
+        footer.extend([0x22, 0x0C, 0x08, 0x0C, 0x12, 0x02, 0x01, 0x02, 0x1A, 0x01, b'a', 0x1A, 0x01, b'b']);
+        footer.extend([0x22, 0x02, 0x08, 0x04, 0x22, 0x02, 0x08, 0x07]);
+        footer.extend([0x30, 0x05]);
+        let postscript = [0x08, footer.len() as u8, 0x10, 0x00, 0x22, 0x02, 0x00, 0x0C];
+        [&b"ORC"[..], &footer, &postscript, &[postscript.len() as u8]].concat()
+    }
+
+    fn avro_long_bytes(value: i64) -> Vec<u8> {
+        let mut zigzag = ((value << 1) ^ (value >> 63)) as u64;
+        let mut bytes = Vec::new();
+        while zigzag >= 0x80 {
+            bytes.push(zigzag as u8 | 0x80);
+            zigzag >>= 7;
+        }
+        bytes.push(zigzag as u8);
+        bytes
+    }
+
+    /// An Avro file with one block of 3 records.
+    fn avro_file() -> Vec<u8> {
+        let schema = br#"{"type":"record","name":"User","fields":[{"name":"id","type":"long"},{"name":"email","type":["null","string"]}]}"#;
+        let mut file = b"Obj\x01".to_vec();
+        file.extend(avro_long_bytes(2));
+        for (key, value) in [(&b"avro.schema"[..], &schema[..]), (b"avro.codec", b"null")] {
+            file.extend(avro_long_bytes(key.len() as i64));
+            file.extend(key);
+            file.extend(avro_long_bytes(value.len() as i64));
+            file.extend(value);
+        }
+        file.push(0);
+        file.extend([0xAB; 16]);
+        file.extend(avro_long_bytes(3));
+        file.extend(avro_long_bytes(4));
+        file.extend([0; 4]);
+        file.extend([0xAB; 16]);
+        file
+    }
+
+    #[test]
+    fn reports_parquet_schema() {
+        assert_eq!(
+            inspect_bytes("data.parquet", &parquet_file()),
+            expected(&[
+                ("Format", "Parquet"),
+                ("Format Version", "1"),
+                ("Created By", "test"),
+                ("Rows", "10"),
+                ("Row Groups", "0"),
+                ("Columns", "2"),
+                ("Column", "id: INT64"),
+                ("Column", "name: BYTE_ARRAY (UTF8)"),
+            ])
+        );
+    }
+
+    #[test]
+    fn reports_orc_schema() {
+        assert_eq!(
+            inspect_bytes("data.orc", &orc_file()),
+            expected(&[
+                ("Format", "ORC"),
+                ("Format Version", "0.12"),
+                ("Compression", "none"),
+                ("Rows", "5"),
+                ("Stripes", "1"),
+                ("Columns", "2"),
+                ("Column", "a: bigint"),
+                ("Column", "b: string"),
+            ])
+        );
+    }
+
+    #[test]
+    fn reports_avro_schema() {
+        assert_eq!(
+            inspect_bytes("data.avro", &avro_file()),
+            expected(&[
+                ("Format", "Avro"),
+                ("Codec", "null"),
+                ("Rows", "3"),
+                ("Blocks", "1"),
+                ("Schema", "User"),
+                ("Columns", "2"),
+                ("Column", "id: long"),
+                ("Column", "email: null | string"),
+            ])
+        );
+    }
+
+    #[test]
+    fn malformed_files_report_parse_error() {
+        // A cut Parquet trailer, an ORC postscript longer than the file and a
+        // cut Avro header.
+        let parquet = parquet_file();
+        let mut orc = orc_file();
+        *orc.last_mut().unwrap() = 0xFF;
+        let avro = avro_file();
+        let samples = [
+            ("truncated.parquet", &parquet[..parquet.len() - 3]),
+            ("postscript.orc", &orc[..]),
+            ("truncated.avro", &avro[..40]),
+        ];
+        for (name, data) in samples {
+            assert_eq!(inspect_bytes(name, data)[0].0, "Parse Error", "{}", name);
+        }
+    }
+
+    /// An Arrow file whose footer holds `footer`.
+    fn arrow_file(footer: &[u8]) -> Vec<u8> {
+        let mut file = b"ARROW1\0\0".to_vec();
+        file.extend_from_slice(footer);
+        file.extend_from_slice(&(footer.len() as u32).to_le_bytes());
+        file.extend_from_slice(b"ARROW1");
+        file
+    }
+
+    #[test]
+    fn arrow_vector_lengths_are_bounded_by_the_data() {
+        // Footer table with field 1 (schema) pointing to a schema table whose
+        // field 1 (fields) is a vector claiming 0xFFFFFFFF entries.
+        let mut footer = Vec::new();
+        footer.extend_from_slice(&12u32.to_le_bytes());
+        footer.extend_from_slice(&[8, 0, 8, 0, 0, 0, 4, 0]);
+        footer.extend_from_slice(&8i32.to_le_bytes());
+        footer.extend_from_slice(&12u32.to_le_bytes());
+        footer.extend_from_slice(&[8, 0, 8, 0, 0, 0, 4, 0]);
+        footer.extend_from_slice(&8i32.to_le_bytes());
+        footer.extend_from_slice(&4u32.to_le_bytes());
+        footer.extend_from_slice(&u32::MAX.to_le_bytes());
+
+        let details = inspect(&Source::from_bytes("arrow-vector-length", &arrow_file(&footer))).unwrap();
+        assert!(details.contains(&("Format", "Arrow IPC".to_string())));
+        assert!(details.contains(&("Columns", "0".to_string())), "{:?}", details);
+    }
+
+    #[test]
+    fn orc_inflate_stops_at_the_total_cap() {
+        // Stored chunks of 0x7FFFF bytes each; together they exceed the cap.
+        let chunk_length = 0x7FFFF;
+        let header = [((chunk_length << 1) | 1) as u8, (chunk_length >> 7) as u8, (chunk_length >> 15) as u8];
+        let mut data = Vec::new();
+        for _ in 0..(MAX_METADATA_SIZE / chunk_length + 2) {
+            data.extend_from_slice(&header);
+            data.extend(std::iter::repeat_n(0u8, chunk_length));
+        }
+        assert_eq!(orc_inflate(&data).unwrap().len(), MAX_METADATA_SIZE);
+    }
+}
@@ -0,0 +1,169 @@
+//! Custom signature definitions, read from an INI-style text file:
+//!
+//! ```text
+//! [Acme Data]
+//! bytes = 0: 41 43 4D 45
+//! bytes = 0x10: 01 02
+//! mime = application/x-acme
+//! extensions = acme, acd
+//! ```
+//!
+//! Each section defines one signature. Its first `bytes` line is the magic
+//! number; any further ones must match as well. Lines starting with `#` are
+//! comments.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::cli::{parse_list, parse_number};
+use crate::signatures::{Signature, UNKNOWN_MIME};
+use crate::to_hex_string;
+
+/// Custom magics must end within this many bytes of the start of a file,
+/// as every file's head is read up to the furthest one.
+const MAX_REACH: usize = 1024 * 1024;
+
+/// `$XDG_CONFIG_HOME/magicheck/definitions`, falling back to `~/.config`.
+pub fn default_path() -> Option<PathBuf> {
+    let base = match env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("magicheck").join("definitions"))
+}
+
+/// A section being read, before it becomes a signature.
+struct Section {
+    name: String,
+    line: usize,
+    parts: Vec<(usize, Vec<u8>)>,
+    mime: Option<String>,
+    extensions: Vec<String>,
+}
+
+impl Section {
+    /// Builds the signature; its strings and bytes live for the rest of the run.
+    fn into_signature(self) -> Result<Signature, String> {
+        let mut parts = self
+            .parts
+            .into_iter()
+            .map(|(offset, bytes)| (offset, &*Vec::leak(bytes)));
+        let Some((offset, magic)) = parts.next() else {
+            return Err(format!("line {}: [{}] has no bytes", self.line, self.name));
+        };
+        let extensions: Vec<&'static str> = self.extensions.into_iter().map(String::leak).map(|e| &*e).collect();
+        Ok(Signature {
+            offset,
+            magic,
+            name: String::leak(self.name),
+            mime: self.mime.map_or(UNKNOWN_MIME, |mime| String::leak(mime)),
+            extensions: Vec::leak(extensions),
+            secret: None,
+            end_window: None,
+            also: Vec::leak(parts.collect()),
+            verify: None,
+            inspect: None,
+        })
+    }
+}
+
+/// Parses `OFFSET: HEX BYTES`, e.g. `0x10: 01 02`.
+fn parse_bytes(value: &str) -> Result<(usize, Vec<u8>), String> {
+    let (offset, hex) = value
+        .split_once(':')
+        .ok_or_else(|| format!("expected OFFSET: BYTES, found '{}'", value))?;
+    let offset = usize::try_from(parse_number(offset.trim())?).map_err(|_| format!("offset '{}' is too large", offset))?;
+    let digits: String = hex.chars().filter(|c| !c.is_whitespace()).collect();
+    // Checked up front: `from_str_radix` would take a sign, and slicing a non-ASCII digit would panic
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("invalid hex bytes '{}'", hex.trim()));
+    }
+    if digits.is_empty() || !digits.len().is_multiple_of(2) {
+        return Err(format!("'{}' is not a whole number of hex bytes", hex.trim()));
+    }
+    let bytes = digits
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| std::str::from_utf8(pair).ok().and_then(|pair| u8::from_str_radix(pair, 16).ok()))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(|| format!("invalid hex bytes '{}'", hex.trim()))?;
+    if offset.checked_add(bytes.len()).is_none_or(|end| end > MAX_REACH) {
+        return Err(format!("bytes at offset {} end beyond the first {} bytes", offset, MAX_REACH));
+    }
+    Ok((offset, bytes))
+}
+
+fn parse(text: &str) -> Result<Vec<Signature>, String> {
+    let mut sections: Vec<Section> = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let (number, line) = (index + 1, line.trim());
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+            sections.push(Section {
+                name: name.trim().to_string(),
+                line: number,
+                parts: Vec::new(),
+                mime: None,
+                extensions: Vec::new(),
+            });
+            continue;
+        }
+        let section = sections
+            .last_mut()
+            .ok_or_else(|| format!("line {}: expected a [name] section first", number))?;
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("line {}: expected key = value", number))?;
+        let value = value.trim();
+        match key.trim() {
+            "bytes" => section.parts.push(parse_bytes(value).map_err(|e| format!("line {}: {}", number, e))?),
+            "mime" => section.mime = Some(value.to_string()),
+            "extensions" => section.extensions = parse_list(value),
+            other => return Err(format!("line {}: unknown key '{}'", number, other)),
+        }
+    }
+    sections.into_iter().map(Section::into_signature).collect()
+}
+
+/// Reads the signatures defined in `path`; malformed files give `InvalidData`.
+pub fn load(path: &Path) -> io::Result<Vec<Signature>> {
+    parse(&fs::read_to_string(path)?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Formats one section in the form `load` reads back.
+pub fn format_section(name: &str, parts: &[(usize, Vec<u8>)], mime: &str, extensions: &[String]) -> String {
+    let mut section = format!("[{}]\n", name);
+    for (offset, bytes) in parts {
+        section.push_str(&format!("bytes = {}: {}\n", offset, to_hex_string(bytes)));
+    }
+    section.push_str(&format!("mime = {}\n", mime));
+    if !extensions.is_empty() {
+        section.push_str(&format!("extensions = {}\n", extensions.join(", ")));
+    }
+    section
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn well_formed_section_parses() {
+        let signatures = parse("[Acme]\nbytes = 0: 41 43\nbytes = 0x10: ff\nmime = application/x-acme\n").unwrap();
+        assert_eq!(signatures.len(), 1);
+        assert_eq!((signatures[0].offset, signatures[0].magic), (0, &b"AC"[..]));
+        assert_eq!(signatures[0].also, &[(0x10, &[0xFF][..])]);
+    }
+
+    #[test]
+    fn malformed_bytes_are_errors() {
+        for value in ["0: 1é1", "0: +1", "0: 4", "0: zz", "0:", "41 43", "0x10000000: 01"] {
+            assert!(parse_bytes(value).is_err(), "{:?} was accepted", value);
+        }
+        assert!(parse("[Acme]\nbytes = 0: 1é1\n").is_err());
+    }
+}
@@ -0,0 +1,519 @@
+use std::borrow::Cow;
+use std::io;
+
+use crate::bytes::{u16_be, u16_le, u32_be, u32_le, u64_be, u64_le};
+use crate::reader::Source;
+use crate::signatures::Details;
+
+const SECTOR_SIZE: u64 = 512;
+const MBR_PARTITION_TABLE: usize = 0x1BE;
+const EXT_SUPERBLOCK: u64 = 0x400;
+const BTRFS_SUPERBLOCK: u64 = 0x10000;
+const VHDX_REGION_TABLE: u64 = 0x30000;
+/// GPT headers sit in LBA 1, whose offset depends on the logical sector size.
+const GPT_SECTOR_SIZES: &[u64] = &[512, 4096];
+/// Partition arrays larger than this are not listed.
+const MAX_GPT_ENTRIES: u32 = 1024;
+/// GPT entries are 128 bytes times a power of two; larger ones are not plausible.
+const GPT_ENTRY_SIZES: std::ops::RangeInclusive<usize> = 128..=4096;
+/// ext block sizes run from 1 KiB (0) to 64 KiB (6).
+const MAX_EXT_LOG_BLOCK_SIZE: u32 = 6;
+
+const EXT_COMPAT_HAS_JOURNAL: u32 = 0x0004;
+const EXT_INCOMPAT_EXTENTS: u32 = 0x0040;
+const EXT_INCOMPAT_64BIT: u32 = 0x0080;
+const EXT_INCOMPAT_FLEX_BG: u32 = 0x0200;
+
+const GPT_TYPES: &[(&str, &str)] = &[
+    ("C12A7328-F81F-11D2-BA4B-00A0C93EC93B", "EFI System"),
+    ("21686148-6449-6E6F-744E-656564454649", "BIOS boot"),
+    ("E3C9E316-0B5C-4DB8-817D-F92DF00215AE", "Microsoft reserved"),
+    ("EBD0A0A2-B9E5-4433-87C0-68B6B72699C7", "Microsoft basic data"),
+    ("DE94BBA4-06D1-4D40-A16A-BFD50179D6AC", "Windows recovery"),
+    ("0FC63DAF-8483-4772-8E79-3D69D8477DE4", "Linux filesystem"),
+    ("4F68BCE3-E8CD-4DB1-96E7-FBCAF984B709", "Linux root (x86-64)"),
+    ("0657FD6D-A4AB-43C4-84E5-0933C84B4F4F", "Linux swap"),
+    ("E6D6D379-F507-44C2-A23C-238F2A3DF928", "Linux LVM"),
+    ("A19D880F-05FC-4D3B-A006-743F0F84911E", "Linux RAID"),
+    ("CA7D7CCB-63ED-4C53-861C-1742536059CC", "Linux LUKS"),
+    ("933AC7E1-2EB4-4F13-B844-0E14E2AEF915", "Linux home"),
+    ("48465300-0000-11AA-AA11-00306543ECAC", "Apple HFS+"),
+    ("7C3457EF-0000-11AA-AA11-00306543ECAC", "Apple APFS"),
+    ("516E7CB6-6ECF-11D6-8FF8-00022D09712B", "FreeBSD"),
+];
+
+const MBR_TYPES: &[(u8, &str)] = &[
+    (0x01, "FAT12"),
+    (0x04, "FAT16 <32M"),
+    (0x05, "Extended"),
+    (0x06, "FAT16"),
+    (0x07, "NTFS/exFAT/HPFS"),
+    (0x0B, "FAT32"),
+    (0x0C, "FAT32 (LBA)"),
+    (0x0E, "FAT16 (LBA)"),
+    (0x0F, "Extended (LBA)"),
+    (0x27, "Windows recovery"),
+    (0x82, "Linux swap"),
+    (0x83, "Linux"),
+    (0x85, "Linux extended"),
+    (0x8E, "Linux LVM"),
+    (0xA5, "FreeBSD"),
+    (0xA6, "OpenBSD"),
+    (0xA9, "NetBSD"),
+    (0xAF, "Apple HFS/HFS+"),
+    (0xEE, "GPT protective"),
+    (0xEF, "EFI System"),
+    (0xFD, "Linux RAID"),
+];
+
+fn read(source: &Source, offset: u64, length: usize) -> Option<Cow<'_, [u8]>> {
+    source.range(offset, length).ok().filter(|data| data.len() == length)
+}
+
+/// Formats a mixed-endian GUID as stored by GPT and VHDX.
+fn guid(bytes: &[u8]) -> Option<String> {
+    let b: [u8; 16] = bytes.get(..16)?.try_into().ok()?;
+    Some(format!(
+        "{:08X}-{:04X}-{:04X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}",
+        u32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+        u16::from_le_bytes([b[4], b[5]]),
+        u16::from_le_bytes([b[6], b[7]]),
+        b[8], b[9], b[10], b[11], b[12], b[13], b[14], b[15]
+    ))
+}
+
+/// Formats a big-endian (RFC 4122 byte order) UUID as used by Linux filesystems.
+fn uuid(bytes: &[u8]) -> Option<String> {
+    let hex: String = bytes.get(..16)?.iter().map(|b| format!("{:02x}", b)).collect();
+    Some(format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..]))
+}
+
+fn label(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).trim().to_string()
+}
+
+fn utf16_label(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .take_while(|&unit| unit != 0)
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["KiB", "MiB", "GiB", "TiB", "PiB"];
+    if bytes < 1024 {
+        return format!("{} bytes", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {} ({} bytes)", value, UNITS[unit], bytes)
+}
+
+fn push_label(details: &mut Details, name: String) {
+    if !name.is_empty() {
+        details.push(("Label", name));
+    }
+}
+
+/// Whether the four MBR partition entries look like a partition table rather than boot code.
+pub fn is_mbr(buffer: &[u8]) -> bool {
+    let Some(table) = buffer.get(MBR_PARTITION_TABLE..MBR_PARTITION_TABLE + 64) else {
+        return false;
+    };
+    let entries: Vec<&[u8]> = table.chunks_exact(16).collect();
+    entries.iter().all(|entry| entry[0] == 0x00 || entry[0] == 0x80)
+        && entries.iter().any(|entry| entry[4] != 0 && u32_le(entry, 12).is_some_and(|sectors| sectors > 0))
+}
+
+/// Whether the ext superblock fields around the magic hold plausible values.
+pub fn is_ext(buffer: &[u8]) -> bool {
+    let superblock = |offset| u32_le(buffer, EXT_SUPERBLOCK as usize + offset);
+    superblock(0x18).is_some_and(|log_block_size| log_block_size <= MAX_EXT_LOG_BLOCK_SIZE)
+        && superblock(0x4C).is_some_and(|revision| revision <= 1)
+        && superblock(0x04).is_some_and(|blocks| blocks > 0)
+}
+
+fn mbr_details(sector: &[u8]) -> Option<Details> {
+    let mut details = vec![("Partition Table", "MBR".to_string())];
+    let mut partitions = Vec::new();
+    for (index, entry) in sector.get(MBR_PARTITION_TABLE..MBR_PARTITION_TABLE + 64)?.chunks_exact(16).enumerate() {
+        let (kind, start, sectors) = (entry[4], u32_le(entry, 8)?, u32_le(entry, 12)?);
+        if kind == 0 || sectors == 0 {
+            continue;
+        }
+        let name = MBR_TYPES.iter().find(|(known, _)| *known == kind).map_or("unknown", |(_, name)| name);
+        let mut description = format!(
+            "{}: {} (0x{:02x}), start sector {}, {}",
+            index + 1,
+            name,
+            kind,
+            start,
+            format_size(sectors as u64 * SECTOR_SIZE)
+        );
+        if entry[0] == 0x80 {
+            description.push_str(", bootable");
+        }
+        partitions.push(description);
+    }
+    details.push(("Partitions", partitions.len().to_string()));
+    details.extend(partitions.into_iter().map(|partition| ("Partition", partition)));
+    Some(details)
+}
+
+fn gpt_details(source: &Source, sector_size: u64) -> Option<Details> {
+    let header = read(source, sector_size, 92)?;
+    if !header.starts_with(b"EFI PART") {
+        return None;
+    }
+    let entries_lba = u64_le(&header, 72)?;
+    let entry_count = u32_le(&header, 80)?.min(MAX_GPT_ENTRIES);
+    let entry_size = u32_le(&header, 84)? as usize;
+    if !GPT_ENTRY_SIZES.contains(&entry_size) || !entry_size.is_multiple_of(8) {
+        return None;
+    }
+
+    let mut details = vec![
+        ("Partition Table", "GPT".to_string()),
+        ("Sector Size", sector_size.to_string()),
+        ("Disk GUID", guid(&header[56..72])?),
+    ];
+    let mut partitions = Vec::new();
+    let table = source.range(entries_lba.checked_mul(sector_size)?, entry_count as usize * entry_size).ok()?;
+    for (index, entry) in table.chunks_exact(entry_size).enumerate() {
+        if entry[..16].iter().all(|&b| b == 0) {
+            continue;
+        }
+        let type_guid = guid(&entry[..16])?;
+        let kind = GPT_TYPES.iter().find(|(known, _)| *known == type_guid).map_or(type_guid.as_str(), |(_, name)| name);
+        let (first, last) = (u64_le(entry, 32)?, u64_le(entry, 40)?);
+        let mut description = format!(
+            "{}: {}, start sector {}, {}",
+            index + 1,
+            kind,
+            first,
+            format_size(last.saturating_sub(first).checked_add(1)?.checked_mul(sector_size)?)
+        );
+        let name = utf16_label(&entry[56..128]);
+        if !name.is_empty() {
+            description.push_str(&format!(", \"{}\"", name));
+        }
+        partitions.push(description);
+    }
+    details.push(("Partitions", partitions.len().to_string()));
+    details.extend(partitions.into_iter().map(|partition| ("Partition", partition)));
+    Some(details)
+}
+
+fn ext_details(source: &Source) -> Option<Details> {
+    let sb = read(source, EXT_SUPERBLOCK, 1024)?;
+    // The MBR signature leads here without `is_ext` having checked the superblock
+    let log_block_size = u32_le(&sb, 0x18)?;
+    if log_block_size > MAX_EXT_LOG_BLOCK_SIZE {
+        return None;
+    }
+    let block_size = 1024u64 << log_block_size;
+    let (compat, incompat) = (u32_le(&sb, 0x5C)?, u32_le(&sb, 0x60)?);
+    let mut blocks = u32_le(&sb, 0x04)? as u64;
+    if incompat & EXT_INCOMPAT_64BIT != 0 {
+        blocks |= (u32_le(&sb, 0x150)? as u64) << 32;
+    }
+    let variant = if incompat & (EXT_INCOMPAT_EXTENTS | EXT_INCOMPAT_64BIT | EXT_INCOMPAT_FLEX_BG) != 0 {
+        "ext4"
+    } else if compat & EXT_COMPAT_HAS_JOURNAL != 0 {
+        "ext3"
+    } else {
+        "ext2"
+    };
+    let state = match u16_le(&sb, 0x3A)? {
+        1 => "clean".to_string(),
+        2 => "errors detected".to_string(),
+        other => format!("not clean (0x{:x})", other),
+    };
+
+    let mut details = vec![("Filesystem", variant.to_string())];
+    push_label(&mut details, label(&sb[0x78..0x88]));
+    details.extend([
+        ("UUID", uuid(&sb[0x68..0x78])?),
+        ("Block Size", block_size.to_string()),
+        ("Size", format_size(blocks.checked_mul(block_size)?)),
+        ("Inodes", u32_le(&sb, 0x00)?.to_string()),
+        ("State", state),
+    ]);
+    Some(details)
+}
+
+fn xfs_details(sb: &[u8]) -> Option<Details> {
+    let block_size = u32_be(sb, 4)? as u64;
+    let mut details = vec![("Filesystem", format!("XFS v{}", u16_be(sb, 100)? & 0x000F))];
+    push_label(&mut details, label(sb.get(108..120)?));
+    details.extend([
+        ("UUID", uuid(sb.get(32..48)?)?),
+        ("Block Size", block_size.to_string()),
+        ("Size", format_size(u64_be(sb, 8)?.checked_mul(block_size)?)),
+    ]);
+    Some(details)
+}
+
+fn btrfs_details(source: &Source) -> Option<Details> {
+    let sb = read(source, BTRFS_SUPERBLOCK, 0x22B)?;
+    let mut details = vec![("Filesystem", "Btrfs".to_string())];
+    push_label(&mut details, label(&sb[0x12B..0x22B]));
+    details.extend([
+        ("UUID", uuid(&sb[0x20..0x30])?),
+        ("Size", format_size(u64_le(&sb, 0x70)?)),
+        ("Used", format_size(u64_le(&sb, 0x78)?)),
+        ("Devices", u64_le(&sb, 0x88)?.to_string()),
+        ("Sector Size", u32_le(&sb, 0x90)?.to_string()),
+        ("Node Size", u32_le(&sb, 0x94)?.to_string()),
+    ]);
+    Some(details)
+}
+
+fn squashfs_details(sb: &[u8]) -> Option<Details> {
+    let big_endian = sb.starts_with(b"sqsh");
+    let (major, minor) = if big_endian { (u16_be(sb, 28)?, u16_be(sb, 30)?) } else { (u16_le(sb, 28)?, u16_le(sb, 30)?) };
+    let mut details = vec![("Filesystem", format!("SquashFS {}.{}", major, minor))];
+    // Only version 4 has a stable little-endian layout; older images are just versioned.
+    if major == 4 && !big_endian {
+        let compression = match u16_le(sb, 20)? {
+            1 => "gzip".to_string(),
+            2 => "lzma".to_string(),
+            3 => "lzo".to_string(),
+            4 => "xz".to_string(),
+            5 => "lz4".to_string(),
+            6 => "zstd".to_string(),
+            other => format!("unknown ({})", other),
+        };
+        details.extend([
+            ("Compression", compression),
+            ("Block Size", u32_le(sb, 12)?.to_string()),
+            ("Inodes", u32_le(sb, 4)?.to_string()),
+            ("Size", format_size(u64_le(sb, 40)?)),
+        ]);
+    }
+    Some(details)
+}
+
+/// Pulls a string value out of the LUKS2 JSON area without a full JSON parser.
+fn json_string<'a>(json: &'a str, key: &str) -> Option<&'a str> {
+    let rest = &json[json.find(&format!("\"{}\"", key))? + key.len() + 2..];
+    let rest = &rest[rest.find('"')? + 1..];
+    Some(&rest[..rest.find('"')?])
+}
+
+fn luks_details(source: &Source, header: &[u8]) -> Option<Details> {
+    let version = u16_be(header, 6)?;
+    let mut details = vec![("Filesystem", format!("LUKS{} encrypted volume", version))];
+    match version {
+        1 => {
+            let active_slots = (0..8).filter(|slot| u32_be(header, 208 + slot * 48) == Some(0x00AC_71F3)).count();
+            details.extend([
+                ("UUID", label(header.get(168..208)?)),
+                ("Cipher", format!("{}-{}", label(header.get(8..40)?), label(header.get(40..72)?))),
+                ("Hash", label(header.get(72..104)?)),
+                ("Key Size", format!("{} bits", u64::from(u32_be(header, 108)?) * 8)),
+                ("Key Slots", format!("{} of 8 active", active_slots)),
+            ]);
+        }
+        2 => {
+            push_label(&mut details, label(header.get(24..72)?));
+            details.push(("UUID", label(header.get(168..208)?)));
+            let header_size = u64_be(header, 8)?.min(4 * 1024 * 1024);
+            let json = read(source, 4096, header_size.saturating_sub(4096) as usize)?;
+            let json = String::from_utf8_lossy(&json);
+            if let Some(cipher) = json_string(&json, "encryption") {
+                details.push(("Cipher", cipher.to_string()));
+            }
+            let keyslots = json.find("\"keyslots\"").map(|start| json[start..].matches("\"type\":\"luks2\"").count());
+            if let Some(count) = keyslots {
+                details.push(("Key Slots", count.to_string()));
+            }
+        }
+        _ => {}
+    }
+    Some(details)
+}
+
+fn swap_details(source: &Source, page_size: u64) -> Option<Details> {
+    let header = read(source, 1024, 44)?;
+    let last_page = u32_le(&header, 4)? as u64;
+    let mut details = vec![("Filesystem", format!("Linux swap v{}", u32_le(&header, 0)?))];
+    push_label(&mut details, label(&header[28..44]));
+    details.extend([
+        ("UUID", uuid(&header[12..28])?),
+        ("Page Size", page_size.to_string()),
+        ("Size", format_size((last_page + 1) * page_size)),
+    ]);
+    Some(details)
+}
+
+fn vhd_details(footer: &[u8]) -> Option<Details> {
+    let disk_type = match u32_be(footer, 60)? {
+        2 => "fixed".to_string(),
+        3 => "dynamic".to_string(),
+        4 => "differencing".to_string(),
+        other => format!("unknown ({})", other),
+    };
+    Some(vec![
+        ("Disk Type", disk_type),
+        ("Virtual Size", format_size(u64_be(footer, 48)?)),
+        ("Creator", label(footer.get(28..32)?)),
+        ("UUID", uuid(footer.get(68..84)?)?),
+    ])
+}
+
+fn vhdx_details(source: &Source, head: &[u8]) -> Option<Details> {
+    const METADATA_REGION: &str = "8B7CA206-4790-4B9A-B8FE-575F050F886E";
+    const VIRTUAL_DISK_SIZE: &str = "2FA54224-CD1B-4876-B211-5DBED83BF4B8";
+    const LOGICAL_SECTOR_SIZE: &str = "8141BF1D-A96F-4709-BA47-F233A8FAAB5F";
+
+    let mut details = vec![("Creator", utf16_label(head.get(8..520)?))];
+    let regions = read(source, VHDX_REGION_TABLE, 64 * 1024)?;
+    if !regions.starts_with(b"regi") {
+        return Some(details);
+    }
+    // Counts are untrusted: entries stop where the table does
+    let metadata = (0..u32_le(&regions, 8)? as usize)
+        .map_while(|i| regions.get(16 + i * 32..48 + i * 32))
+        .find(|entry| guid(entry).as_deref() == Some(METADATA_REGION))
+        .and_then(|entry| u64_le(entry, 16));
+    let Some(metadata_offset) = metadata else {
+        return Some(details);
+    };
+
+    let table = read(source, metadata_offset, 64 * 1024)?;
+    if !table.starts_with(b"metadata") {
+        return Some(details);
+    }
+    let item = |wanted: &str| -> Option<Vec<u8>> {
+        let entry = (0..u16_le(&table, 10)? as usize)
+            .map_while(|i| table.get(32 + i * 32..64 + i * 32))
+            .find(|entry| guid(entry).as_deref() == Some(wanted))?;
+        read(source, metadata_offset.checked_add(u32_le(entry, 16)? as u64)?, u32_le(entry, 20)? as usize).map(Cow::into_owned)
+    };
+    if let Some(size) = item(VIRTUAL_DISK_SIZE).and_then(|data| u64_le(&data, 0)) {
+        details.push(("Virtual Size", format_size(size)));
+    }
+    if let Some(sector) = item(LOGICAL_SECTOR_SIZE).and_then(|data| u32_le(&data, 0)) {
+        details.push(("Sector Size", sector.to_string()));
+    }
+    Some(details)
+}
+
+/// Describes partition tables, filesystem superblocks and virtual disk headers.
+pub fn inspect(source: &Source) -> io::Result<Details> {
+    let head = source.range(0, 4096)?;
+    let details = if head.starts_with(b"LUKS\xBA\xBE") {
+        luks_details(source, &head)
+    } else if head.starts_with(b"XFSB") {
+        xfs_details(&head)
+    } else if head.starts_with(b"hsqs") || head.starts_with(b"sqsh") {
+        squashfs_details(&head)
+    } else if head.starts_with(b"conectix") {
+        vhd_details(&head)
+    } else if head.starts_with(b"vhdxfile") {
+        vhdx_details(source, &head)
+    } else if let Some(details) = GPT_SECTOR_SIZES.iter().find_map(|&size| gpt_details(source, size)) {
+        Some(details)
+    } else if read(source, BTRFS_SUPERBLOCK + 0x40, 8).as_deref() == Some(b"_BHRfS_M") {
+        btrfs_details(source)
+    } else if read(source, EXT_SUPERBLOCK + 0x38, 2).as_deref() == Some(&[0x53, 0xEF]) {
+        ext_details(source)
+    } else if let Some(page_size) = [4096u64, 8192, 16384, 65536]
+        .into_iter()
+        .find(|&size| read(source, size - 10, 10).as_deref() == Some(b"SWAPSPACE2"))
+    {
+        swap_details(source, page_size)
+    } else if is_mbr(&head) && head.get(0x1FE..0x200) == Some(&[0x55, 0xAA]) {
+        mbr_details(&head)
+    } else {
+        None
+    };
+    Ok(details.unwrap_or_else(|| vec![("Parse Error", "truncated or malformed disk image header".to_string())]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn put(image: &mut [u8], offset: usize, bytes: &[u8]) {
+        image[offset..offset + bytes.len()].copy_from_slice(bytes);
+    }
+
+    fn gpt_image(entries_lba: u64, entry_count: u32, entry_size: u32) -> Vec<u8> {
+        let mut image = vec![0; 2048];
+        put(&mut image, 512, b"EFI PART");
+        put(&mut image, 512 + 72, &entries_lba.to_le_bytes());
+        put(&mut image, 512 + 80, &entry_count.to_le_bytes());
+        put(&mut image, 512 + 84, &entry_size.to_le_bytes());
+        image
+    }
+
+    #[test]
+    fn gpt_with_implausible_entries_is_rejected() {
+        for (name, image) in [
+            ("gpt-entry-size", gpt_image(2, 1024, u32::MAX)),
+            ("gpt-entry-align", gpt_image(2, 4, 130)),
+            ("gpt-entries-lba", gpt_image(u64::MAX, 4, 128)),
+        ] {
+            assert!(gpt_details(&Source::from_bytes(name, &image), 512).is_none(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn gpt_partition_size_overflow_is_rejected() {
+        let mut image = gpt_image(2, 1, 128);
+        put(&mut image, 1024, &[1; 16]);
+        put(&mut image, 1024 + 40, &u64::MAX.to_le_bytes());
+        assert!(gpt_details(&Source::from_bytes("gpt-partition-size", &image), 512).is_none());
+    }
+
+    fn ext_image(log_block_size: u32, blocks: u32, blocks_high: u32) -> Vec<u8> {
+        let mut image = vec![0; 2048];
+        let sb = EXT_SUPERBLOCK as usize;
+        put(&mut image, sb + 0x04, &blocks.to_le_bytes());
+        put(&mut image, sb + 0x18, &log_block_size.to_le_bytes());
+        put(&mut image, sb + 0x38, &[0x53, 0xEF]);
+        put(&mut image, sb + 0x60, &EXT_INCOMPAT_64BIT.to_le_bytes());
+        put(&mut image, sb + 0x150, &blocks_high.to_le_bytes());
+        image
+    }
+
+    #[test]
+    fn ext_with_implausible_geometry_is_rejected() {
+        for (name, image) in [
+            ("ext-log-block-size", ext_image(u32::MAX, 1, 0)),
+            ("ext-size", ext_image(MAX_EXT_LOG_BLOCK_SIZE, u32::MAX, u32::MAX)),
+        ] {
+            let source = Source::from_bytes(name, &image);
+            assert!(ext_details(&source).is_none(), "{}", name);
+            assert_eq!(inspect(&source).unwrap()[0].0, "Parse Error", "{}", name);
+        }
+    }
+
+    #[test]
+    fn xfs_size_overflow_is_rejected() {
+        let mut sb = vec![0; 512];
+        put(&mut sb, 0, b"XFSB");
+        put(&mut sb, 4, &u32::MAX.to_be_bytes());
+        put(&mut sb, 8, &u64::MAX.to_be_bytes());
+        assert!(xfs_details(&sb).is_none());
+    }
+
+    #[test]
+    fn vhdx_region_count_is_bounded_by_the_table() {
+        let mut image = vec![0; VHDX_REGION_TABLE as usize + 64 * 1024];
+        put(&mut image, 0, b"vhdxfile");
+        put(&mut image, VHDX_REGION_TABLE as usize, b"regi");
+        put(&mut image, VHDX_REGION_TABLE as usize + 8, &u32::MAX.to_le_bytes());
+        let details = vhdx_details(&Source::from_bytes("vhdx-region-count", &image), &image[..4096]).unwrap();
+        assert_eq!(details.len(), 1);
+    }
+}
@@ -0,0 +1,202 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// Size of the blocks used for the per-block entropy profile.
+pub const BLOCK_SIZE: usize = 4096;
+
+/// Entropy above this (in bits per byte) is treated as random-looking data.
+const HIGH_ENTROPY_THRESHOLD: f64 = 7.5;
+/// Entropy below this is treated as sparse, mostly repeated data.
+const LOW_ENTROPY_THRESHOLD: f64 = 1.0;
+
+/// Byte statistics gathered over a whole file.
+pub struct EntropyReport {
+    pub total_bytes: u64,
+    pub overall: f64,
+    pub blocks: Vec<f64>,
+    pub zero_ratio: f64,
+    pub text_ratio: f64,
+}
+
+impl EntropyReport {
+    /// Number of blocks whose entropy looks encrypted or compressed.
+    pub fn high_entropy_blocks(&self) -> usize {
+        self.blocks.iter().filter(|&&e| e >= HIGH_ENTROPY_THRESHOLD).count()
+    }
+
+    /// A short human readable guess about the nature of the data.
+    pub fn classify(&self) -> &'static str {
+        if self.total_bytes == 0 {
+            "empty"
+        } else if self.zero_ratio >= 0.9 || self.overall < LOW_ENTROPY_THRESHOLD {
+            "sparse/zeroed"
+        } else if self.text_ratio >= 0.95 {
+            "likely text"
+        } else if self.overall >= HIGH_ENTROPY_THRESHOLD {
+            "likely encrypted or compressed"
+        } else if self.high_entropy_blocks() > 0 {
+            "binary data with encrypted or compressed regions"
+        } else {
+            "likely structured binary data"
+        }
+    }
+}
+
+/// Shannon entropy in bits per byte of a byte histogram.
+fn shannon_entropy(counts: &[u64; 256], total: u64) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    let total = total as f64;
+    counts
+        .iter()
+        .filter(|&&c| c > 0)
+        .map(|&c| {
+            let p = c as f64 / total;
+            p * (1.0 / p).log2()
+        })
+        .sum()
+}
+
+/// Counts bytes that belong to text: printable ASCII, whitespace and complete
+/// UTF-8 sequences. Fed byte by byte so sequences may span blocks.
+#[derive(Default)]
+struct TextCounter {
+    text_bytes: u64,
+    /// Bytes of the UTF-8 sequence read so far.
+    pending: u64,
+    /// Continuation bytes the sequence still needs.
+    needed: u8,
+    /// Range the next continuation byte must fall in.
+    next: (u8, u8),
+}
+
+impl TextCounter {
+    fn push(&mut self, b: u8) {
+        if self.needed > 0 {
+            if (self.next.0..=self.next.1).contains(&b) {
+                self.pending += 1;
+                self.needed -= 1;
+                self.next = (0x80, 0xBF);
+                if self.needed == 0 {
+                    self.text_bytes += self.pending;
+                }
+                return;
+            }
+            // A truncated sequence is not text; `b` may still start one.
+            self.needed = 0;
+        }
+        // The second-byte ranges exclude overlong forms, surrogates and
+        // code points above U+10FFFF.
+        let (needed, next) = match b {
+            b'\t' | b'\n' | b'\r' | 0x20..=0x7E => {
+                self.text_bytes += 1;
+                return;
+            }
+            0xC2..=0xDF => (1, (0x80, 0xBF)),
+            0xE0 => (2, (0xA0, 0xBF)),
+            0xED => (2, (0x80, 0x9F)),
+            0xE1..=0xEF => (2, (0x80, 0xBF)),
+            0xF0 => (3, (0x90, 0xBF)),
+            0xF1..=0xF3 => (3, (0x80, 0xBF)),
+            0xF4 => (3, (0x80, 0x8F)),
+            _ => return,
+        };
+        self.pending = 1;
+        self.needed = needed;
+        self.next = next;
+    }
+}
+
+/// Streams the file once, computing whole-file and per-block entropy.
+pub fn analyze_file(file_path: &Path) -> io::Result<EntropyReport> {
+    analyze(File::open(file_path)?)
+}
+
+fn analyze(mut file: impl Read) -> io::Result<EntropyReport> {
+    let mut buffer = vec![0u8; BLOCK_SIZE];
+    let mut counts = [0u64; 256];
+    let mut total_bytes = 0u64;
+    let mut blocks = Vec::new();
+    let mut text = TextCounter::default();
+
+    loop {
+        // Fill a whole block so block boundaries do not depend on read sizes.
+        let mut filled = 0;
+        while filled < BLOCK_SIZE {
+            let n = file.read(&mut buffer[filled..])?;
+            if n == 0 {
+                break;
+            }
+            filled += n;
+        }
+        if filled == 0 {
+            break;
+        }
+
+        let mut block_counts = [0u64; 256];
+        for &b in &buffer[..filled] {
+            block_counts[b as usize] += 1;
+            text.push(b);
+        }
+        for (total, block) in counts.iter_mut().zip(block_counts.iter()) {
+            *total += block;
+        }
+        blocks.push(shannon_entropy(&block_counts, filled as u64));
+        total_bytes += filled as u64;
+
+        if filled < BLOCK_SIZE {
+            break;
+        }
+    }
+
+    let ratio = |n: u64| if total_bytes == 0 { 0.0 } else { n as f64 / total_bytes as f64 };
+
+    Ok(EntropyReport {
+        total_bytes,
+        overall: shannon_entropy(&counts, total_bytes),
+        blocks,
+        zero_ratio: ratio(counts[0]),
+        text_ratio: ratio(text.text_bytes),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify(data: &[u8]) -> &'static str {
+        analyze(data).unwrap().classify()
+    }
+
+    #[test]
+    fn classifies_zeroed_random_and_text_data() {
+        assert_eq!(classify(&[0; 3 * BLOCK_SIZE]), "sparse/zeroed");
+
+        // xorshift64: uniform bytes, and mostly not valid UTF-8.
+        let mut state = 0x9E37_79B9_7F4A_7C15u64;
+        let random: Vec<u8> = (0..4 * BLOCK_SIZE)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                (state >> 56) as u8
+            })
+            .collect();
+        assert_eq!(classify(&random), "likely encrypted or compressed");
+
+        let ascii = "The quick brown fox jumps over the lazy dog.\n".repeat(200);
+        assert_eq!(classify(ascii.as_bytes()), "likely text");
+        let utf8 = "Grüße aus Köln, 東京 ✓\n".repeat(200);
+        assert_eq!(classify(utf8.as_bytes()), "likely text");
+    }
+
+    #[test]
+    fn high_bytes_outside_utf8_sequences_are_not_text() {
+        // Latin-1 "ü", a lone continuation byte, an overlong "/" and a
+        // sequence cut short by ASCII: only the ASCII counts.
+        let report = analyze(&b"\xFC\x80\xC0\xAF\xE2\x9Cab"[..]).unwrap();
+        assert_eq!(report.text_ratio, 2.0 / 8.0);
+    }
+}
@@ -0,0 +1,181 @@
+use std::io::{self, Read};
+
+use flate2::read::ZlibDecoder;
+
+use crate::bytes::u32_be;
+use crate::reader::Source;
+use crate::signatures::Details;
+
+/// Bundle headers list one line per reference; larger headers are cut off.
+const MAX_BUNDLE_HEADER: usize = 1024 * 1024;
+/// Only this many bundle references are listed individually.
+const MAX_LISTED_REFERENCES: usize = 100;
+/// A loose object header is `<type> <size>\0`, well within this.
+const MAX_OBJECT_HEADER: u64 = 64;
+const OBJECT_TYPES: &[&str] = &["commit", "tree", "blob", "tag"];
+
+/// Inflates the start of a zlib stream and splits off a loose object's `<type> <size>` header.
+fn object_header(buffer: &[u8]) -> Option<(&'static str, u64)> {
+    let mut header = Vec::new();
+    // A truncated stream still yields its leading bytes, so errors past the header are fine.
+    let _ = ZlibDecoder::new(buffer).take(MAX_OBJECT_HEADER).read_to_end(&mut header);
+    let end = header.iter().position(|&b| b == 0)?;
+    let (kind, size) = std::str::from_utf8(&header[..end]).ok()?.split_once(' ')?;
+    let kind = OBJECT_TYPES.iter().find(|known| **known == kind)?;
+    Some((kind, size.parse().ok()?))
+}
+
+/// Whether `buffer` is a zlib stream holding a git loose object.
+pub fn is_loose_object(buffer: &[u8]) -> bool {
+    object_header(buffer).is_some()
+}
+
+fn pack_details(head: &[u8]) -> Option<Details> {
+    Some(vec![
+        ("Git Object", "packfile".to_string()),
+        ("Version", u32_be(head, 4)?.to_string()),
+        ("Objects", u32_be(head, 8)?.to_string()),
+    ])
+}
+
+fn pack_index_details(head: &[u8]) -> Option<Details> {
+    // The last fan-out entry counts every object with a first byte up to 0xff.
+    Some(vec![
+        ("Git Object", "pack index".to_string()),
+        ("Version", u32_be(head, 4)?.to_string()),
+        ("Objects", u32_be(head, 8 + 255 * 4)?.to_string()),
+    ])
+}
+
+fn index_details(head: &[u8]) -> Option<Details> {
+    Some(vec![
+        ("Git Object", "index (staging area)".to_string()),
+        ("Version", u32_be(head, 4)?.to_string()),
+        ("Entries", u32_be(head, 8)?.to_string()),
+    ])
+}
+
+fn bundle_details(head: &[u8]) -> Option<Details> {
+    let text = String::from_utf8_lossy(head);
+    let mut lines = text.lines();
+    let version = lines.next()?.strip_prefix("# ")?.strip_suffix(" git bundle")?.to_string();
+
+    let mut details = vec![("Git Object", "bundle".to_string()), ("Version", version)];
+    let (mut prerequisites, mut references) = (0, Vec::new());
+    for line in lines.take_while(|line| !line.is_empty()) {
+        if let Some(capability) = line.strip_prefix('@') {
+            // v3 capabilities such as @object-format=sha256
+            details.push(("Capability", capability.to_string()));
+        } else if line.starts_with('-') {
+            prerequisites += 1;
+        } else if let Some((_, name)) = line.split_once(' ') {
+            references.push(name.to_string());
+        }
+    }
+    details.push(("Prerequisites", prerequisites.to_string()));
+    details.push(("References", references.len().to_string()));
+    let hidden = references.len().saturating_sub(MAX_LISTED_REFERENCES);
+    details.extend(references.into_iter().take(MAX_LISTED_REFERENCES).map(|name| ("Reference", name)));
+    if hidden > 0 {
+        details.push(("Reference", format!("... and {} more", hidden)));
+    }
+    Some(details)
+}
+
+fn loose_object_details(head: &[u8]) -> Option<Details> {
+    let (kind, size) = object_header(head)?;
+    Some(vec![
+        ("Git Object", "loose object".to_string()),
+        ("Object Type", kind.to_string()),
+        ("Object Size", size.to_string()),
+    ])
+}
+
+/// Describes git packfiles, pack indices, bundles, index files and loose objects.
+pub fn inspect(source: &Source) -> io::Result<Details> {
+    let head = source.range(0, MAX_BUNDLE_HEADER)?;
+    let details = if head.starts_with(b"PACK") {
+        pack_details(&head)
+    } else if head.starts_with(b"\xFFtOc") {
+        pack_index_details(&head)
+    } else if head.starts_with(b"DIRC") {
+        index_details(&head)
+    } else if head.starts_with(b"# v") {
+        bundle_details(&head)
+    } else {
+        loose_object_details(&head)
+    };
+    Ok(details.unwrap_or_else(|| vec![("Parse Error", "truncated or malformed git header".to_string())]))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+
+    use super::*;
+
+    fn inspect_bytes(name: &str, data: &[u8]) -> Details {
+        inspect(&Source::from_bytes(name, data)).unwrap()
+    }
+
+    fn expected(pairs: &[(&'static str, &str)]) -> Details {
+        pairs.iter().map(|&(key, value)| (key, value.to_string())).collect()
+    }
+
+    #[test]
+    fn reports_loose_object() {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(b"blob 12\0hello world\n").unwrap();
+        let object = encoder.finish().unwrap();
+        assert!(is_loose_object(&object));
+        assert_eq!(
+            inspect_bytes("loose-object", &object),
+            expected(&[("Git Object", "loose object"), ("Object Type", "blob"), ("Object Size", "12")])
+        );
+    }
+
+    #[test]
+    fn reports_bundle_references() {
+        let bundle = format!(
+            "# v3 git bundle\n@object-format=sha1\n-{0} base\n{0} refs/heads/main\n{0} refs/tags/v1\n\nPACK",
+            "1".repeat(40)
+        );
+        assert_eq!(
+            inspect_bytes("repo.bundle", bundle.as_bytes()),
+            expected(&[
+                ("Git Object", "bundle"),
+                ("Version", "v3"),
+                ("Capability", "object-format=sha1"),
+                ("Prerequisites", "1"),
+                ("References", "2"),
+                ("Reference", "refs/heads/main"),
+                ("Reference", "refs/tags/v1"),
+            ])
+        );
+    }
+
+    #[test]
+    fn reports_packfile() {
+        let pack = [&b"PACK"[..], &2u32.to_be_bytes(), &7u32.to_be_bytes()].concat();
+        assert_eq!(
+            inspect_bytes("objects.pack", &pack),
+            expected(&[("Git Object", "packfile"), ("Version", "2"), ("Objects", "7")])
+        );
+    }
+
+    #[test]
+    fn truncated_headers_report_parse_error() {
+        // A pack index cut inside its fan-out table, zlib data that is no
+        // object and a packfile header cut before its object count.
+        let index = [&b"\xFFtOc"[..], &2u32.to_be_bytes(), &[0; 64]].concat();
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(b"not a git object").unwrap();
+        let not_object = encoder.finish().unwrap();
+        for (name, data) in [("truncated.idx", &index[..]), ("not-an-object", &not_object[..]), ("short.pack", &b"PACK\0\0"[..])] {
+            assert_eq!(inspect_bytes(name, data)[0].0, "Parse Error", "{}", name);
+        }
+    }
+}
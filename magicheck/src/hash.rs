@@ -0,0 +1,96 @@
+//! Content digests, computed from the same read that identifies a file.
+
+use std::io::{self, Write};
+
+use md5::Md5;
+use sha2::{Digest, Sha256};
+
+/// A digest `--hash` can compute.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Algorithm {
+    Sha256,
+    Md5,
+    Blake3,
+}
+
+impl Algorithm {
+    /// The name used on the command line and as the JSON and CSV key.
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Sha256 => "sha256",
+            Algorithm::Md5 => "md5",
+            Algorithm::Blake3 => "blake3",
+        }
+    }
+
+    /// The name shown in text reports.
+    pub fn label(self) -> &'static str {
+        match self {
+            Algorithm::Sha256 => "SHA-256",
+            Algorithm::Md5 => "MD5",
+            Algorithm::Blake3 => "BLAKE3",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Algorithm> {
+        [Algorithm::Sha256, Algorithm::Md5, Algorithm::Blake3]
+            .into_iter()
+            .find(|algorithm| algorithm.name().eq_ignore_ascii_case(name))
+    }
+}
+
+enum State {
+    Sha256(Sha256),
+    Md5(Md5),
+    Blake3(Box<blake3::Hasher>),
+}
+
+/// Feeds every byte written to it to each requested digest.
+pub struct Hasher {
+    states: Vec<State>,
+}
+
+impl Hasher {
+    pub fn new(algorithms: &[Algorithm]) -> Hasher {
+        let states = algorithms
+            .iter()
+            .map(|algorithm| match algorithm {
+                Algorithm::Sha256 => State::Sha256(Sha256::new()),
+                Algorithm::Md5 => State::Md5(Md5::new()),
+                Algorithm::Blake3 => State::Blake3(Box::new(blake3::Hasher::new())),
+            })
+            .collect();
+        Hasher { states }
+    }
+
+    /// The lowercase hex digests, in the order the algorithms were requested.
+    pub fn finish(self) -> Vec<(Algorithm, String)> {
+        self.states
+            .into_iter()
+            .map(|state| match state {
+                State::Sha256(state) => (Algorithm::Sha256, format!("{:x}", state.finalize())),
+                State::Md5(state) => (Algorithm::Md5, format!("{:x}", state.finalize())),
+                State::Blake3(state) => (Algorithm::Blake3, state.finalize().to_hex().to_string()),
+            })
+            .collect()
+    }
+}
+
+impl Write for Hasher {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for state in &mut self.states {
+            match state {
+                State::Sha256(state) => state.update(buf),
+                State::Md5(state) => state.update(buf),
+                State::Blake3(state) => {
+                    state.update(buf);
+                }
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+use std::ops::Range;
+
+const BYTES_PER_LINE: usize = 16;
+const HIGHLIGHT_START: &str = "\x1b[1;31m";
+const HIGHLIGHT_END: &str = "\x1b[0m";
+
+fn paint(text: String, highlighted: bool, color: bool) -> String {
+    if highlighted && color {
+        format!("{}{}{}", HIGHLIGHT_START, text, HIGHLIGHT_END)
+    } else {
+        text
+    }
+}
+
+/// Renders `bytes` (located at `base_offset` in the file) in the style of `xxd`.
+///
+/// Bytes whose file offset falls in `highlight` are painted when `color` is set.
+pub fn hex_dump(bytes: &[u8], base_offset: u64, highlight: Option<&Range<u64>>, color: bool) -> String {
+    let is_highlighted = |offset: u64| highlight.is_some_and(|range| range.contains(&offset));
+    let mut out = String::new();
+
+    for (line_index, line) in bytes.chunks(BYTES_PER_LINE).enumerate() {
+        let line_offset = base_offset + (line_index * BYTES_PER_LINE) as u64;
+        out.push_str(&format!("{:08x}: ", line_offset));
+
+        for column in 0..BYTES_PER_LINE {
+            match line.get(column) {
+                Some(&b) => {
+                    let hex = format!("{:02x}", b);
+                    out.push_str(&paint(hex, is_highlighted(line_offset + column as u64), color));
+                }
+                None => out.push_str("  "),
+            }
+            if column % 2 == 1 {
+                out.push(' ');
+            }
+        }
+
+        out.push(' ');
+        for (column, &b) in line.iter().enumerate() {
+            let ch = if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' };
+            out.push_str(&paint(ch.to_string(), is_highlighted(line_offset + column as u64), color));
+        }
+        out.push('\n');
+    }
+
+    out
+}
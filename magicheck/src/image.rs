@@ -0,0 +1,626 @@
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::io;
+
+use crate::bytes::{u16_be, u16_endian, u16_le, u32_be, u32_endian, u32_le};
+use crate::reader::Source;
+use crate::signatures::Details;
+
+/// Image headers and the chunks or segments ahead of the pixel data fit in this.
+const HEAD_SIZE: usize = 1024 * 1024;
+/// Upper bound on how much of an animation is scanned when counting frames.
+const MAX_SCAN_SIZE: usize = 64 * 1024 * 1024;
+/// Guards against IFD chains that loop or never end.
+const MAX_TIFF_PAGES: usize = 4096;
+
+const PNG_MAGIC: &[u8] = &[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+const EXR_MAGIC: &[u8] = &[0x76, 0x2F, 0x31, 0x01];
+
+const TIFF_IMAGE_WIDTH: u16 = 256;
+const TIFF_IMAGE_LENGTH: u16 = 257;
+const TIFF_BITS_PER_SAMPLE: u16 = 258;
+const TIFF_COMPRESSION: u16 = 259;
+const TIFF_PHOTOMETRIC: u16 = 262;
+const TIFF_ORIENTATION: u16 = 274;
+const TIFF_SAMPLES_PER_PIXEL: u16 = 277;
+
+/// Whether a RIFF buffer holds a WebP image rather than audio or video.
+pub fn is_webp(buffer: &[u8]) -> bool {
+    buffer.get(8..12) == Some(b"WEBP")
+}
+
+fn dimensions(width: impl std::fmt::Display, height: impl std::fmt::Display) -> (&'static str, String) {
+    ("Dimensions", format!("{}x{}", width, height))
+}
+
+fn orientation_name(orientation: u32) -> String {
+    match orientation {
+        1 => "normal".to_string(),
+        2 => "mirrored horizontally".to_string(),
+        3 => "rotated 180°".to_string(),
+        4 => "mirrored vertically".to_string(),
+        5 => "mirrored horizontally, rotated 270° clockwise".to_string(),
+        6 => "rotated 90° clockwise".to_string(),
+        7 => "mirrored horizontally, rotated 90° clockwise".to_string(),
+        8 => "rotated 270° clockwise".to_string(),
+        other => format!("invalid ({})", other),
+    }
+}
+
+/// One image file directory of a TIFF structure.
+struct Ifd {
+    entries: Vec<(u16, u16, u32, [u8; 4])>,
+    next: u32,
+}
+
+/// Random access into a TIFF structure held in memory or on disk.
+struct Tiff<'a> {
+    read_at: &'a dyn Fn(u64, usize) -> Option<Vec<u8>>,
+    big_endian: bool,
+}
+
+impl Tiff<'_> {
+    fn header(&self) -> Option<u32> {
+        let header = (self.read_at)(0, 8)?;
+        let expected: &[u8] = if self.big_endian { b"MM\0*" } else { b"II*\0" };
+        if header.get(..4)? != expected {
+            return None;
+        }
+        u32_endian(&header, 4, self.big_endian)
+    }
+
+    fn ifd(&self, offset: u32) -> Option<Ifd> {
+        let count = u16_endian(&(self.read_at)(offset as u64, 2)?, 0, self.big_endian)? as usize;
+        let data = (self.read_at)(offset as u64 + 2, count * 12 + 4)?;
+        let mut entries = Vec::with_capacity(count);
+        for i in 0..count {
+            let entry = data.get(i * 12..i * 12 + 12)?;
+            entries.push((
+                u16_endian(entry, 0, self.big_endian)?,
+                u16_endian(entry, 2, self.big_endian)?,
+                u32_endian(entry, 4, self.big_endian)?,
+                entry[8..12].try_into().ok()?,
+            ));
+        }
+        Some(Ifd { entries, next: u32_endian(&data, count * 12, self.big_endian).unwrap_or(0) })
+    }
+
+    /// First value of a SHORT or LONG tag, following the offset when it does not fit inline.
+    fn value(&self, ifd: &Ifd, tag: u16) -> Option<u32> {
+        let &(_, field_type, count, raw) = ifd.entries.iter().find(|entry| entry.0 == tag)?;
+        let size = match field_type {
+            3 => 2,
+            4 => 4,
+            _ => return None,
+        };
+        let data = if size * count as usize <= 4 {
+            raw.to_vec()
+        } else {
+            (self.read_at)(u32_endian(&raw, 0, self.big_endian)? as u64, size)?
+        };
+        match size {
+            2 => u16_endian(&data, 0, self.big_endian).map(u32::from),
+            _ => u32_endian(&data, 0, self.big_endian),
+        }
+    }
+
+    /// Orientation tag of the first IFD, as stored in Exif blocks.
+    fn orientation(&self) -> Option<u32> {
+        let ifd = self.ifd(self.header()?)?;
+        self.value(&ifd, TIFF_ORIENTATION)
+    }
+}
+
+fn tiff_in_memory(data: &[u8]) -> Option<u32> {
+    let read_at = |offset: u64, length: usize| data.get(offset as usize..offset as usize + length).map(<[u8]>::to_vec);
+    let big_endian = data.starts_with(b"MM");
+    Tiff { read_at: &read_at, big_endian }.orientation()
+}
+
+fn tiff_details(source: &Source, head: &[u8]) -> Option<Details> {
+    let read_at = |offset: u64, length: usize| {
+        source.range(offset, length).ok().filter(|data| data.len() == length).map(Cow::into_owned)
+    };
+    let tiff = Tiff { read_at: &read_at, big_endian: head.starts_with(b"MM") };
+    let first = tiff.ifd(tiff.header()?)?;
+
+    let mut pages = 1;
+    let mut seen = HashSet::new();
+    let mut next = first.next;
+    while next != 0 && pages < MAX_TIFF_PAGES && seen.insert(next) {
+        let Some(ifd) = tiff.ifd(next) else { break };
+        pages += 1;
+        next = ifd.next;
+    }
+
+    let samples = tiff.value(&first, TIFF_SAMPLES_PER_PIXEL).unwrap_or(1);
+    let color = match (tiff.value(&first, TIFF_PHOTOMETRIC), samples) {
+        (Some(0 | 1), 1) => "Grayscale",
+        (Some(0 | 1), _) => "Grayscale+Alpha",
+        (Some(2), 3) => "RGB",
+        (Some(2), _) => "RGBA",
+        (Some(3), _) => "Indexed",
+        (Some(4), _) => "Transparency mask",
+        (Some(5), _) => "CMYK",
+        (Some(6), _) => "YCbCr",
+        (Some(8), _) => "CIELab",
+        _ => "unknown",
+    };
+    let compression = match tiff.value(&first, TIFF_COMPRESSION).unwrap_or(1) {
+        1 => "none".to_string(),
+        2 => "CCITT RLE".to_string(),
+        3 => "CCITT Group 3".to_string(),
+        4 => "CCITT Group 4".to_string(),
+        5 => "LZW".to_string(),
+        6 | 7 => "JPEG".to_string(),
+        8 | 32946 => "Deflate".to_string(),
+        32773 => "PackBits".to_string(),
+        other => format!("unknown ({})", other),
+    };
+
+    let mut details = vec![
+        dimensions(tiff.value(&first, TIFF_IMAGE_WIDTH)?, tiff.value(&first, TIFF_IMAGE_LENGTH)?),
+        ("Bit Depth", tiff.value(&first, TIFF_BITS_PER_SAMPLE).unwrap_or(1).to_string()),
+        ("Color Type", color.to_string()),
+        ("Compression", compression),
+        ("Pages", pages.to_string()),
+    ];
+    if let Some(orientation) = tiff.value(&first, TIFF_ORIENTATION) {
+        details.push(("Orientation", orientation_name(orientation)));
+    }
+    Some(details)
+}
+
+fn png_details(data: &[u8]) -> Option<Details> {
+    if data.get(12..16)? != b"IHDR" {
+        return None;
+    }
+    let color = match *data.get(25)? {
+        0 => "Grayscale",
+        2 => "RGB",
+        3 => "Indexed",
+        4 => "Grayscale+Alpha",
+        6 => "RGBA",
+        _ => "invalid",
+    };
+    let mut details = vec![
+        dimensions(u32_be(data, 16)?, u32_be(data, 20)?),
+        ("Bit Depth", data.get(24)?.to_string()),
+        ("Color Type", color.to_string()),
+        ("Interlaced", if *data.get(28)? == 1 { "yes (Adam7)" } else { "no" }.to_string()),
+    ];
+
+    // Animation control and Exif chunks must precede the image data.
+    let mut frames = None;
+    let mut orientation = None;
+    let mut pos = PNG_MAGIC.len();
+    while let (Some(length), Some(kind)) = (u32_be(data, pos), data.get(pos + 4..pos + 8)) {
+        let body = data.get(pos + 8..pos + 8 + length as usize);
+        match kind {
+            b"acTL" => frames = body.and_then(|body| u32_be(body, 0)),
+            b"eXIf" => orientation = body.and_then(tiff_in_memory),
+            b"IDAT" | b"IEND" => break,
+            _ => {}
+        }
+        pos += 12 + length as usize;
+    }
+
+    match frames {
+        Some(frames) => details.push(("Frames", format!("{} (APNG)", frames))),
+        None => details.push(("Frames", "1".to_string())),
+    }
+    if let Some(orientation) = orientation {
+        details.push(("Orientation", orientation_name(orientation)));
+    }
+    Some(details)
+}
+
+fn jpeg_details(data: &[u8]) -> Option<Details> {
+    let mut details = None;
+    let mut orientation = None;
+    let mut pos = 2;
+
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xFF {
+            return None;
+        }
+        let marker = data[pos + 1];
+        match marker {
+            // Fill bytes and markers without a length field.
+            0xFF => {
+                pos += 1;
+                continue;
+            }
+            0x01 | 0xD0..=0xD7 => {
+                pos += 2;
+                continue;
+            }
+            _ => {}
+        }
+        let length = u16_be(data, pos + 2)? as usize;
+        let segment = data.get(pos + 4..pos + 2 + length);
+
+        match marker {
+            0xC0..=0xCF if !matches!(marker, 0xC4 | 0xC8 | 0xCC) => {
+                let segment = segment?;
+                let process = match marker {
+                    0xC0 => "baseline",
+                    0xC2 | 0xC6 | 0xCA | 0xCE => "progressive",
+                    0xC3 | 0xC7 | 0xCB | 0xCF => "lossless",
+                    _ => "extended sequential",
+                };
+                let color = match segment.get(5)? {
+                    1 => "Grayscale",
+                    3 => "YCbCr",
+                    4 => "CMYK",
+                    _ => "unknown",
+                };
+                details = Some(vec![
+                    dimensions(u16_be(segment, 3)?, u16_be(segment, 1)?),
+                    ("Bit Depth", segment.first()?.to_string()),
+                    ("Color Type", color.to_string()),
+                    ("Encoding", process.to_string()),
+                ]);
+            }
+            0xE1 => {
+                if let Some(exif) = segment.and_then(|s| s.strip_prefix(b"Exif\0\0")) {
+                    orientation = tiff_in_memory(exif);
+                }
+            }
+            0xDA | 0xD9 => break,
+            _ => {}
+        }
+        pos += 2 + length;
+    }
+
+    let mut details = details?;
+    details.push(("Frames", "1".to_string()));
+    if let Some(orientation) = orientation {
+        details.push(("Orientation", orientation_name(orientation)));
+    }
+    Some(details)
+}
+
+/// Skips a chain of GIF data sub-blocks, returning the position after the terminator.
+fn skip_gif_sub_blocks(data: &[u8], mut pos: usize) -> Option<usize> {
+    loop {
+        let size = *data.get(pos)? as usize;
+        pos += 1 + size;
+        if size == 0 {
+            return Some(pos);
+        }
+    }
+}
+
+fn gif_details(data: &[u8]) -> Option<Details> {
+    let packed = *data.get(10)?;
+    let mut details = vec![
+        ("Version", String::from_utf8_lossy(data.get(3..6)?).into_owned()),
+        dimensions(u16_le(data, 6)?, u16_le(data, 8)?),
+        ("Bit Depth", (((packed >> 4) & 0x07) + 1).to_string()),
+        ("Color Type", "Indexed".to_string()),
+    ];
+
+    let color_table_size = |packed: u8| if packed & 0x80 != 0 { 3 << ((packed & 0x07) + 1) } else { 0 };
+    let mut pos = 13 + color_table_size(packed);
+    let mut frames = 0;
+    let mut complete = false;
+    while let Some(&block) = data.get(pos) {
+        match block {
+            0x2C => {
+                frames += 1;
+                let Some(&local) = data.get(pos + 9) else { break };
+                pos += 10 + color_table_size(local) + 1;
+                match skip_gif_sub_blocks(data, pos) {
+                    Some(next) => pos = next,
+                    None => break,
+                }
+            }
+            0x21 => match skip_gif_sub_blocks(data, pos + 2) {
+                Some(next) => pos = next,
+                None => break,
+            },
+            0x3B => {
+                complete = true;
+                break;
+            }
+            _ => break,
+        }
+    }
+
+    let frames = if complete { frames.to_string() } else { format!("{} (scan incomplete)", frames) };
+    details.push(("Frames", frames));
+    Some(details)
+}
+
+fn bmp_details(data: &[u8]) -> Option<Details> {
+    let header_size = u32_le(data, 14)?;
+    let (width, height, bits, compression) = match header_size {
+        12 => (u16_le(data, 18)? as i64, u16_le(data, 20)? as i64, u16_le(data, 24)?, 0),
+        40.. => (
+            u32_le(data, 18)? as i32 as i64,
+            u32_le(data, 22)? as i32 as i64,
+            u16_le(data, 28)?,
+            u32_le(data, 30)?,
+        ),
+        _ => return None,
+    };
+    let color = match bits {
+        1 | 2 | 4 | 8 => "Indexed",
+        16 | 24 => "RGB",
+        32 => "RGBA",
+        _ => "unknown",
+    };
+    let compression = match compression {
+        0 => "none".to_string(),
+        1 => "RLE8".to_string(),
+        2 => "RLE4".to_string(),
+        3 | 6 => "bit fields".to_string(),
+        4 => "JPEG".to_string(),
+        5 => "PNG".to_string(),
+        other => format!("unknown ({})", other),
+    };
+    Some(vec![
+        dimensions(width, height.abs()),
+        ("Bit Depth", bits.to_string()),
+        ("Color Type", color.to_string()),
+        ("Compression", compression),
+        ("Orientation", if height < 0 { "top-down" } else { "bottom-up" }.to_string()),
+    ])
+}
+
+fn u24_le(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 3)?;
+    Some(bytes[0] as u32 | (bytes[1] as u32) << 8 | (bytes[2] as u32) << 16)
+}
+
+fn webp_details(data: &[u8]) -> Option<Details> {
+    let mut details = Vec::new();
+    let mut frames = 0;
+    let mut orientation = None;
+    let mut pos = 12;
+
+    while let (Some(kind), Some(length)) = (data.get(pos..pos + 4), u32_le(data, pos + 4)) {
+        let body = data.get(pos + 8..pos + 8 + length as usize);
+        match kind {
+            b"VP8 " if details.is_empty() => {
+                let body = body?;
+                if body.get(3..6)? != [0x9D, 0x01, 0x2A] {
+                    return None;
+                }
+                details.push(dimensions(u16_le(body, 6)? & 0x3FFF, u16_le(body, 8)? & 0x3FFF));
+                details.push(("Color Type", "YUV 4:2:0 (lossy)".to_string()));
+            }
+            b"VP8L" if details.is_empty() => {
+                let body = body?;
+                if *body.first()? != 0x2F {
+                    return None;
+                }
+                let bits = u32_le(body, 1)?;
+                details.push(dimensions((bits & 0x3FFF) + 1, ((bits >> 14) & 0x3FFF) + 1));
+                let color = if bits & (1 << 28) != 0 { "RGBA (lossless)" } else { "RGB (lossless)" };
+                details.push(("Color Type", color.to_string()));
+            }
+            b"VP8X" => {
+                let body = body?;
+                details.push(dimensions(u24_le(body, 4)? + 1, u24_le(body, 7)? + 1));
+                let color = if body.first()? & 0x10 != 0 { "RGBA" } else { "RGB" };
+                details.push(("Color Type", color.to_string()));
+            }
+            b"ANMF" => frames += 1,
+            b"EXIF" => orientation = body.and_then(tiff_in_memory),
+            _ => {}
+        }
+        pos += 8 + length as usize + (length as usize & 1);
+    }
+
+    if details.is_empty() {
+        return None;
+    }
+    details.push(("Bit Depth", "8".to_string()));
+    details.push(("Frames", frames.max(1).to_string()));
+    if let Some(orientation) = orientation {
+        details.push(("Orientation", orientation_name(orientation)));
+    }
+    Some(details)
+}
+
+fn qoi_details(data: &[u8]) -> Option<Details> {
+    let color = match data.get(12)? {
+        3 => "RGB",
+        4 => "RGBA",
+        _ => "invalid",
+    };
+    let colorspace = match data.get(13)? {
+        0 => "sRGB with linear alpha",
+        1 => "all channels linear",
+        _ => "invalid",
+    };
+    Some(vec![
+        dimensions(u32_be(data, 4)?, u32_be(data, 8)?),
+        ("Bit Depth", "8".to_string()),
+        ("Color Type", color.to_string()),
+        ("Colorspace", colorspace.to_string()),
+    ])
+}
+
+fn null_terminated(data: &[u8], pos: usize) -> Option<(&[u8], usize)> {
+    let rest = data.get(pos..)?;
+    let end = rest.iter().position(|&b| b == 0)?;
+    Some((&rest[..end], pos + end + 1))
+}
+
+fn exr_details(data: &[u8]) -> Option<Details> {
+    let version = u32_le(data, 4)?;
+    let mut details = vec![("Version", (version & 0xFF).to_string())];
+    let mut parts = Vec::new();
+    if version & 0x200 != 0 {
+        parts.push("tiled");
+    }
+    if version & 0x800 != 0 {
+        parts.push("deep data");
+    }
+    if version & 0x1000 != 0 {
+        parts.push("multi-part");
+    }
+    if !parts.is_empty() {
+        details.push(("Layout", parts.join(", ")));
+    }
+
+    let mut pos = 8;
+    loop {
+        let (name, next) = null_terminated(data, pos)?;
+        if name.is_empty() {
+            break;
+        }
+        let (_, next) = null_terminated(data, next)?;
+        let size = u32_le(data, next)? as usize;
+        let value = data.get(next + 4..next + 4 + size)?;
+        pos = next + 4 + size;
+
+        match name {
+            b"dataWindow" => {
+                let coordinate = |i: usize| u32_le(value, i * 4).map(|v| v as i32 as i64);
+                let (x_min, y_min, x_max, y_max) = (coordinate(0)?, coordinate(1)?, coordinate(2)?, coordinate(3)?);
+                details.push(dimensions(x_max - x_min + 1, y_max - y_min + 1));
+            }
+            b"channels" => {
+                let mut names = Vec::new();
+                let mut depths = Vec::new();
+                let mut channel = 0;
+                while let Some((channel_name, next)) = null_terminated(value, channel).filter(|(n, _)| !n.is_empty()) {
+                    names.push(String::from_utf8_lossy(channel_name).into_owned());
+                    depths.push(match u32_le(value, next)? {
+                        0 => "32 (uint)",
+                        1 => "16 (half float)",
+                        2 => "32 (float)",
+                        _ => "invalid",
+                    });
+                    channel = next + 16;
+                }
+                depths.dedup();
+                details.push(("Bit Depth", depths.join(", ")));
+                details.push(("Color Type", names.join(",")));
+            }
+            b"compression" => {
+                let compression = match value.first()? {
+                    0 => "none",
+                    1 => "RLE",
+                    2 => "ZIPS",
+                    3 => "ZIP",
+                    4 => "PIZ",
+                    5 => "PXR24",
+                    6 => "B44",
+                    7 => "B44A",
+                    8 => "DWAA",
+                    9 => "DWAB",
+                    _ => "unknown",
+                };
+                details.push(("Compression", compression.to_string()));
+            }
+            b"lineOrder" => {
+                let order = match value.first()? {
+                    0 => "increasing Y",
+                    1 => "decreasing Y",
+                    2 => "random Y",
+                    _ => "invalid",
+                };
+                details.push(("Orientation", order.to_string()));
+            }
+            _ => {}
+        }
+    }
+    Some(details)
+}
+
+/// Reports dimensions, bit depth, color type, frames and orientation of images.
+pub fn inspect(source: &Source) -> io::Result<Details> {
+    let head = source.range(0, HEAD_SIZE)?;
+    let details = if head.starts_with(PNG_MAGIC) {
+        png_details(&head)
+    } else if head.starts_with(&[0xFF, 0xD8]) {
+        jpeg_details(&head)
+    } else if head.starts_with(b"GIF8") {
+        gif_details(&source.range(0, MAX_SCAN_SIZE)?)
+    } else if head.starts_with(b"BM") {
+        bmp_details(&head)
+    } else if head.starts_with(b"II*\0") || head.starts_with(b"MM\0*") {
+        tiff_details(source, &head)
+    } else if head.starts_with(b"RIFF") && is_webp(&head) {
+        webp_details(&source.range(0, MAX_SCAN_SIZE)?)
+    } else if head.starts_with(b"qoif") {
+        qoi_details(&head)
+    } else if head.starts_with(EXR_MAGIC) {
+        exr_details(&head)
+    } else {
+        None
+    };
+    Ok(details.unwrap_or_else(|| vec![("Parse Error", "truncated or malformed image header".to_string())]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(kind: &[u8], body: &[u8]) -> Vec<u8> {
+        [&(body.len() as u32).to_be_bytes()[..], kind, body, &[0; 4]].concat()
+    }
+
+    fn inspect_bytes(name: &str, data: &[u8]) -> Details {
+        inspect(&Source::from_bytes(name, data)).unwrap()
+    }
+
+    fn expected(pairs: &[(&'static str, &str)]) -> Details {
+        pairs.iter().map(|&(key, value)| (key, value.to_string())).collect()
+    }
+
+    #[test]
+    fn reports_animated_png() {
+        let ihdr = [&3u32.to_be_bytes()[..], &2u32.to_be_bytes(), &[8, 6, 0, 0, 0]].concat();
+        let png = [
+            PNG_MAGIC.to_vec(),
+            chunk(b"IHDR", &ihdr),
+            chunk(b"acTL", &[0, 0, 0, 4, 0, 0, 0, 0]),
+            chunk(b"IDAT", &[]),
+        ]
+        .concat();
+        assert_eq!(
+            inspect_bytes("image.png", &png),
+            expected(&[
+                ("Dimensions", "3x2"),
+                ("Bit Depth", "8"),
+                ("Color Type", "RGBA"),
+                ("Interlaced", "no"),
+                ("Frames", "4 (APNG)"),
+            ])
+        );
+    }
+
+    #[test]
+    fn reports_baseline_jpeg() {
+        // SOF0: 8-bit, 2 lines of 3 samples, 3 components; then start of scan.
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xC0, 0x00, 0x11, 8, 0, 2, 0, 3, 3];
+        jpeg.extend([0; 9]);
+        jpeg.extend([0xFF, 0xDA, 0x00, 0x02]);
+        assert_eq!(
+            inspect_bytes("image.jpg", &jpeg),
+            expected(&[
+                ("Dimensions", "3x2"),
+                ("Bit Depth", "8"),
+                ("Color Type", "YCbCr"),
+                ("Encoding", "baseline"),
+                ("Frames", "1"),
+            ])
+        );
+    }
+
+    #[test]
+    fn truncated_images_report_parse_error() {
+        let png = [PNG_MAGIC, &[0, 0, 0, 13], b"IHDR", &[0, 0, 0, 3]].concat();
+        // An APP0 segment and nothing describing the frame.
+        let jpeg = [0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x4A, 0x46, 0xFF, 0xD9];
+        for (name, data) in [("truncated.png", &png[..]), ("truncated.jpg", &jpeg[..])] {
+            assert_eq!(inspect_bytes(name, data)[0].0, "Parse Error", "{}", name);
+        }
+    }
+}
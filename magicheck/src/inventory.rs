@@ -0,0 +1,77 @@
+//! Machine-readable check results, one line per file: JSON objects or CSV rows.
+
+use std::fs;
+use std::path::Path;
+
+use serde_json::{json, Map, Value};
+
+use crate::hash::Algorithm;
+use crate::to_hex_string;
+
+/// What is recorded about one file.
+pub struct Entry<'a> {
+    pub path: &'a Path,
+    pub file_type: String,
+    pub mime: &'a str,
+    pub trailer: Option<&'a str>,
+    pub warnings: &'a [String],
+    pub magic: &'a [u8],
+    pub digests: &'a [(Algorithm, String)],
+}
+
+impl Entry<'_> {
+    fn link(&self) -> Option<String> {
+        fs::read_link(self.path).ok().map(|target| target.to_string_lossy().into_owned())
+    }
+
+    /// The entry as one line of JSON, with a key per requested digest.
+    pub fn to_json(&self) -> String {
+        let mut record = Map::new();
+        record.insert("path".into(), json!(self.path.to_string_lossy()));
+        record.insert("link".into(), json!(self.link()));
+        record.insert("type".into(), json!(self.file_type));
+        record.insert("mime".into(), json!(self.mime));
+        record.insert("trailer".into(), json!(self.trailer));
+        record.insert("warnings".into(), json!(self.warnings));
+        record.insert("magic".into(), json!(to_hex_string(self.magic)));
+        for (algorithm, digest) in self.digests {
+            record.insert(algorithm.name().into(), json!(digest));
+        }
+        Value::Object(record).to_string()
+    }
+
+    /// The entry as a CSV row matching `csv_header`; digests not computed
+    /// (for files that were not read) are left empty.
+    pub fn to_csv(&self, algorithms: &[Algorithm]) -> String {
+        let mut fields = vec![
+            self.path.to_string_lossy().into_owned(),
+            self.link().unwrap_or_default(),
+            self.file_type.clone(),
+            self.mime.to_string(),
+            self.trailer.unwrap_or_default().to_string(),
+            self.warnings.join("; "),
+            to_hex_string(self.magic),
+        ];
+        for algorithm in algorithms {
+            let digest = self.digests.iter().find(|(computed, _)| computed == algorithm);
+            fields.push(digest.map_or_else(String::new, |(_, digest)| digest.clone()));
+        }
+        fields.iter().map(|field| csv_field(field)).collect::<Vec<_>>().join(",")
+    }
+}
+
+/// The CSV header row, with a column per requested digest.
+pub fn csv_header(algorithms: &[Algorithm]) -> String {
+    let mut columns = vec!["path", "link", "type", "mime", "trailer", "warnings", "magic"];
+    columns.extend(algorithms.iter().map(|algorithm| algorithm.name()));
+    columns.join(",")
+}
+
+/// Quotes a field per RFC 4180 when it holds a comma, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,74 @@
+//! `magicheck learn`: derives a signature from sample files of one format.
+
+use std::io;
+use std::path::Path;
+
+use crate::cli::LearnOptions;
+use crate::definitions;
+use crate::read_file_range;
+use crate::signatures::UNKNOWN_MIME;
+
+/// How far into each sample stable bytes are looked for.
+const LEARN_WINDOW: usize = 512;
+/// Stable runs past the common prefix shorter than this are taken as coincidence.
+const MIN_STABLE_RUN: usize = 2;
+
+/// Byte runs every sample has at the same offsets: the common prefix (if any)
+/// first, then stable runs further in.
+fn stable_runs(samples: &[Vec<u8>]) -> Vec<(usize, Vec<u8>)> {
+    let length = samples.iter().map(Vec::len).min().unwrap_or(0);
+    let first = &samples[0];
+    let mut runs = Vec::new();
+    let mut start = None;
+    for i in 0..=length {
+        let stable = i < length && samples.iter().all(|sample| sample[i] == first[i]);
+        match (start, stable) {
+            (None, true) => start = Some(i),
+            (Some(from), false) => {
+                if from == 0 || i - from >= MIN_STABLE_RUN {
+                    runs.push((from, first[from..i].to_vec()));
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    runs
+}
+
+/// The extension every sample shares, if any.
+fn common_extension(files: &[String]) -> Option<String> {
+    let mut extensions = files
+        .iter()
+        .map(|file| Path::new(file).extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase));
+    let first = extensions.next()??;
+    extensions.all(|extension| extension.as_deref() == Some(first.as_str())).then_some(first)
+}
+
+/// Reads the samples and formats the learned signature as a definitions section.
+pub fn learn(options: &LearnOptions) -> io::Result<String> {
+    let samples = options
+        .files
+        .iter()
+        .map(|file| read_file_range(Path::new(file), 0, LEARN_WINDOW))
+        .collect::<io::Result<Vec<Vec<u8>>>>()?;
+    let runs = stable_runs(&samples);
+    if runs.is_empty() {
+        return Err(io::Error::other("the samples share no bytes at fixed offsets"));
+    }
+
+    let prefix = runs.first().filter(|(offset, _)| *offset == 0).map_or(0, |(_, bytes)| bytes.len());
+    let further: usize = runs.iter().map(|(_, bytes)| bytes.len()).sum::<usize>() - prefix;
+    let extensions = if options.extensions.is_empty() {
+        common_extension(&options.files).into_iter().collect()
+    } else {
+        options.extensions.clone()
+    };
+    Ok(format!(
+        "# Learned from {} samples: {} byte common prefix, {} further stable bytes\n{}",
+        samples.len(),
+        prefix,
+        further,
+        definitions::format_section(&options.name, &runs, options.mime.as_deref().unwrap_or(UNKNOWN_MIME), &extensions)
+    ))
+}
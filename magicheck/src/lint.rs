@@ -0,0 +1,87 @@
+//! `magicheck signatures --lint`: finds table entries that can never match.
+
+use crate::selftest::{self, Failure};
+use crate::signatures::{self, Signature};
+use crate::to_hex_string;
+
+/// Why an entry of the first-match-wins table never fires.
+pub enum Problem {
+    /// Same magic at the same offset as an earlier entry.
+    Duplicate { index: usize, earlier: usize },
+    /// An earlier entry's magic is a prefix (or other part) of this one's.
+    PrefixConflict { index: usize, earlier: usize },
+    /// Not detected from its samples for another reason.
+    Unreachable(Failure),
+}
+
+/// `#index "name" (bytes at offset)`, naming an entry with its position in
+/// `signatures::all()`; custom entries are marked as such.
+pub fn entry(index: usize) -> String {
+    let signature = signatures::all().nth(index).expect("index within signatures::all()");
+    format!(
+        "#{} {}\"{}\" ({} at offset {})",
+        index,
+        if index < signatures::custom_count() { "custom " } else { "" },
+        signature.name,
+        to_hex_string(signature.magic),
+        signature.offset
+    )
+}
+
+/// Whether every buffer matching `later` also matches `earlier`, so that
+/// `later` is shadowed when listed after it.
+fn covers(earlier: &Signature, later: &Signature) -> bool {
+    earlier.verify.is_none()
+        && earlier.offset >= later.offset
+        && earlier.offset + earlier.magic.len() <= later.offset + later.magic.len()
+        && later.magic[earlier.offset - later.offset..].starts_with(earlier.magic)
+}
+
+impl Problem {
+    pub fn index(&self) -> usize {
+        match self {
+            Problem::Duplicate { index, .. } | Problem::PrefixConflict { index, .. } => *index,
+            Problem::Unreachable(failure) => failure.index,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Problem::Duplicate { index, earlier } => {
+                format!("duplicate: {} repeats {}", entry(*index), entry(*earlier))
+            }
+            Problem::PrefixConflict { index, earlier } => format!(
+                "prefix conflict: {} can never match, {} is listed first and matches a part of it",
+                entry(*index),
+                entry(*earlier)
+            ),
+            Problem::Unreachable(failure) => format!("unreachable: {}", failure.describe()),
+        }
+    }
+}
+
+/// Every problem in the signatures tried, custom ones included, in the order
+/// they are tried.
+pub fn lint() -> Vec<Problem> {
+    let table: Vec<&Signature> = signatures::all().collect();
+    let mut problems: Vec<Problem> = table
+        .iter()
+        .enumerate()
+        .filter_map(|(index, signature)| {
+            let earlier = table[..index].iter().position(|earlier| covers(earlier, signature))?;
+            let shadowing = table[earlier];
+            Some(if shadowing.offset == signature.offset && shadowing.magic == signature.magic {
+                Problem::Duplicate { index, earlier }
+            } else {
+                Problem::PrefixConflict { index, earlier }
+            })
+        })
+        .collect();
+    for failure in selftest::check() {
+        if !problems.iter().any(|problem| problem.index() == failure.index) {
+            problems.push(Problem::Unreachable(failure));
+        }
+    }
+    problems.sort_by_key(Problem::index);
+    problems
+}
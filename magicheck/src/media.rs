@@ -0,0 +1,751 @@
+use std::collections::BTreeMap;
+use std::io;
+
+use crate::bytes::{u16_be, u16_le, u32_be, u32_le};
+use crate::reader::Source;
+use crate::signatures::Details;
+
+/// Container headers (Matroska tracks, AVI hdrl, ID3 tags) fit well within this.
+const HEAD_SIZE: usize = 1024 * 1024;
+/// The last Ogg page, which carries the final granule position, lies within this.
+const OGG_TAIL_SIZE: usize = 64 * 1024;
+/// Standard MIDI files are small; longer files are not worth walking.
+const MAX_MIDI_SIZE: usize = 16 * 1024 * 1024;
+
+/// Whether a RIFF buffer holds WAVE audio.
+pub fn is_wave(buffer: &[u8]) -> bool {
+    buffer.get(8..12) == Some(b"WAVE")
+}
+
+/// Whether a RIFF buffer holds AVI video.
+pub fn is_avi(buffer: &[u8]) -> bool {
+    buffer.get(8..12) == Some(b"AVI ")
+}
+
+fn format_duration(seconds: f64) -> String {
+    let millis = (seconds * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}.{:03} ({:.2} s)",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000,
+        seconds
+    )
+}
+
+fn rate_text(rate: impl std::fmt::Display) -> String {
+    format!("{} Hz", rate)
+}
+
+fn wave_format_name(tag: u16) -> String {
+    match tag {
+        0x0001 => "PCM".to_string(),
+        0x0002 => "Microsoft ADPCM".to_string(),
+        0x0003 => "IEEE float".to_string(),
+        0x0006 => "A-law".to_string(),
+        0x0007 => "µ-law".to_string(),
+        0x0011 => "IMA ADPCM".to_string(),
+        0x0055 => "MPEG Layer 3".to_string(),
+        0x00FF => "AAC".to_string(),
+        0x2000 => "AC-3".to_string(),
+        0xFFFE => "PCM (extensible)".to_string(),
+        other => format!("unknown (0x{:04x})", other),
+    }
+}
+
+fn flac_details(head: &[u8]) -> Option<Details> {
+    // STREAMINFO is always the first metadata block.
+    if head.get(4)? & 0x7F != 0 {
+        return None;
+    }
+    let info = head.get(8..8 + 34)?;
+    let rate = (info[10] as u32) << 12 | (info[11] as u32) << 4 | (info[12] as u32) >> 4;
+    let channels = ((info[12] >> 1) & 0x07) + 1;
+    let bits = (((info[12] & 0x01) << 4) | (info[13] >> 4)) + 1;
+    let samples = ((info[13] & 0x0F) as u64) << 32 | u32_be(info, 14)? as u64;
+
+    let mut details = vec![
+        ("Codec", "FLAC".to_string()),
+        ("Sample Rate", rate_text(rate)),
+        ("Channels", channels.to_string()),
+        ("Bits Per Sample", bits.to_string()),
+    ];
+    if rate > 0 && samples > 0 {
+        details.push(("Duration", format_duration(samples as f64 / rate as f64)));
+    }
+    Some(details)
+}
+
+/// Walks the top-level RIFF chunks, reading only their headers from disk.
+fn riff_chunks(source: &Source, mut visit: impl FnMut(&[u8], u64, u32) -> bool) -> io::Result<()> {
+    let mut pos = 12u64;
+    loop {
+        let header = source.range(pos, 8)?;
+        let (Some(kind), Some(size)) = (header.get(..4), u32_le(&header, 4)) else {
+            return Ok(());
+        };
+        if !visit(kind, pos + 8, size) {
+            return Ok(());
+        }
+        pos += 8 + size as u64 + (size as u64 & 1);
+    }
+}
+
+fn wave_details(source: &Source) -> io::Result<Option<Details>> {
+    let mut format = None;
+    let mut data_size = None;
+    riff_chunks(source, |kind, offset, size| {
+        match kind {
+            b"fmt " => format = source.range(offset, 16).ok(),
+            b"data" => data_size = Some(size),
+            _ => {}
+        }
+        format.is_none() || data_size.is_none()
+    })?;
+
+    let Some(format) = format.filter(|f| f.len() == 16) else {
+        return Ok(None);
+    };
+    let (tag, channels, rate, byte_rate, bits) = (
+        u16_le(&format, 0).unwrap_or(0),
+        u16_le(&format, 2).unwrap_or(0),
+        u32_le(&format, 4).unwrap_or(0),
+        u32_le(&format, 8).unwrap_or(0),
+        u16_le(&format, 14).unwrap_or(0),
+    );
+    let mut details = vec![
+        ("Codec", wave_format_name(tag)),
+        ("Sample Rate", rate_text(rate)),
+        ("Channels", channels.to_string()),
+        ("Bits Per Sample", bits.to_string()),
+    ];
+    if let (Some(size), true) = (data_size, byte_rate > 0) {
+        details.push(("Duration", format_duration(size as f64 / byte_rate as f64)));
+    }
+    Ok(Some(details))
+}
+
+/// Iterates over the chunks of a RIFF LIST body held in memory.
+fn list_chunks(data: &[u8]) -> impl Iterator<Item = (&[u8], &[u8])> {
+    let mut pos = 0;
+    std::iter::from_fn(move || {
+        let kind = data.get(pos..pos + 4)?;
+        let size = u32_le(data, pos + 4)? as usize;
+        let body = data.get(pos + 8..(pos + 8 + size).min(data.len()))?;
+        pos += 8 + size + (size & 1);
+        Some((kind, body))
+    })
+}
+
+fn avi_details(head: &[u8]) -> Option<Details> {
+    let (_, hdrl) = list_chunks(head.get(12..)?).find(|(kind, body)| *kind == b"LIST" && body.starts_with(b"hdrl"))?;
+    let mut details = Vec::new();
+    let mut streams = Vec::new();
+
+    for (kind, body) in list_chunks(&hdrl[4..]) {
+        if kind == b"avih" {
+            let (usec_per_frame, frames) = (u32_le(body, 0)?, u32_le(body, 16)?);
+            details.push(("Duration", format_duration(usec_per_frame as f64 * frames as f64 / 1_000_000.0)));
+            details.push(("Dimensions", format!("{}x{}", u32_le(body, 32)?, u32_le(body, 36)?)));
+        } else if kind == b"LIST" && body.starts_with(b"strl") {
+            let mut stream_type = None;
+            let mut description = String::new();
+            for (kind, body) in list_chunks(&body[4..]) {
+                match kind {
+                    b"strh" => {
+                        stream_type = body.get(..4).map(|t| String::from_utf8_lossy(t).into_owned());
+                        description = String::from_utf8_lossy(body.get(4..8)?).trim_matches(char::from(0)).trim().to_string();
+                    }
+                    b"strf" if stream_type.as_deref() == Some("auds") => {
+                        description = format!(
+                            "{}, {}, {} channels",
+                            wave_format_name(u16_le(body, 0)?),
+                            rate_text(u32_le(body, 4)?),
+                            u16_le(body, 2)?
+                        );
+                    }
+                    _ => {}
+                }
+            }
+            let label = match stream_type.as_deref() {
+                Some("vids") => "video",
+                Some("auds") => "audio",
+                Some("txts") => "subtitle",
+                _ => "other",
+            };
+            streams.push(format!("{} {}", label, description));
+        }
+    }
+
+    details.insert(0, ("Container", "AVI".to_string()));
+    details.push(("Streams", streams.len().to_string()));
+    details.extend(streams.into_iter().map(|stream| ("Stream", stream)));
+    Some(details)
+}
+
+/// One Ogg page: header fields plus its payload.
+struct OggPage<'a> {
+    header_type: u8,
+    granule: i64,
+    serial: u32,
+    payload: &'a [u8],
+    length: usize,
+}
+
+fn ogg_page(data: &[u8]) -> Option<OggPage<'_>> {
+    if !data.starts_with(b"OggS") {
+        return None;
+    }
+    let segments = *data.get(26)? as usize;
+    let table = data.get(27..27 + segments)?;
+    let payload_size: usize = table.iter().map(|&s| s as usize).sum();
+    let start = 27 + segments;
+    Some(OggPage {
+        header_type: *data.get(5)?,
+        granule: i64::from_le_bytes(data.get(6..14)?.try_into().ok()?),
+        serial: u32_le(data, 14)?,
+        payload: data.get(start..(start + payload_size).min(data.len()))?,
+        length: start + payload_size,
+    })
+}
+
+/// An Ogg logical stream identified from its first packet.
+struct OggStream {
+    codec: &'static str,
+    rate: u32,
+    channels: u8,
+    pre_skip: u64,
+    last_granule: i64,
+}
+
+fn ogg_stream(packet: &[u8]) -> OggStream {
+    let mut stream = OggStream { codec: "unknown", rate: 0, channels: 0, pre_skip: 0, last_granule: -1 };
+    if packet.starts_with(b"\x01vorbis") {
+        stream.codec = "Vorbis";
+        stream.channels = packet.get(11).copied().unwrap_or(0);
+        stream.rate = u32_le(packet, 12).unwrap_or(0);
+    } else if packet.starts_with(b"OpusHead") {
+        stream.codec = "Opus";
+        stream.channels = packet.get(9).copied().unwrap_or(0);
+        stream.pre_skip = u16_le(packet, 10).unwrap_or(0) as u64;
+        // Opus granule positions always count 48 kHz samples.
+        stream.rate = 48_000;
+    } else if packet.starts_with(b"\x7FFLAC") {
+        stream.codec = "FLAC";
+        if let Some(info) = packet.get(17..30) {
+            stream.rate = (info[10] as u32) << 12 | (info[11] as u32) << 4 | (info[12] as u32) >> 4;
+            stream.channels = ((info[12] >> 1) & 0x07) + 1;
+        }
+    } else if packet.starts_with(b"Speex   ") {
+        stream.codec = "Speex";
+        stream.rate = u32_le(packet, 36).unwrap_or(0);
+        stream.channels = u32_le(packet, 48).unwrap_or(0) as u8;
+    } else if packet.starts_with(b"\x80theora") {
+        stream.codec = "Theora";
+    } else if packet.starts_with(b"\x80kate\0\0\0") {
+        stream.codec = "Kate";
+    }
+    stream
+}
+
+fn ogg_details(head: &[u8], tail: &[u8]) -> Option<Details> {
+    let mut streams: BTreeMap<u32, OggStream> = BTreeMap::new();
+    let mut order = Vec::new();
+
+    // Every stream starts with a beginning-of-stream page before any data page.
+    let mut pos = 0;
+    while let Some(page) = head.get(pos..).and_then(ogg_page) {
+        if page.header_type & 0x02 == 0 {
+            break;
+        }
+        order.push(page.serial);
+        streams.insert(page.serial, ogg_stream(page.payload));
+        pos += page.length;
+    }
+    if streams.is_empty() {
+        return None;
+    }
+
+    let mut pos = 0;
+    while let Some(offset) = tail.get(pos..).and_then(|rest| rest.windows(4).position(|w| w == b"OggS")) {
+        pos += offset;
+        if let Some(page) = ogg_page(&tail[pos..]).filter(|page| page.granule >= 0)
+            && let Some(stream) = streams.get_mut(&page.serial)
+        {
+            stream.last_granule = page.granule;
+        }
+        pos += 4;
+    }
+
+    let mut details = vec![("Container", "Ogg".to_string()), ("Streams", streams.len().to_string())];
+    let mut duration: Option<f64> = None;
+    for serial in order {
+        let stream = &streams[&serial];
+        let mut description = stream.codec.to_string();
+        if stream.rate > 0 && stream.codec != "Opus" {
+            description.push_str(&format!(", {}", rate_text(stream.rate)));
+        }
+        if stream.channels > 0 {
+            description.push_str(&format!(", {} channels", stream.channels));
+        }
+        details.push(("Stream", description));
+        if stream.rate > 0 && stream.last_granule > 0 {
+            let samples = (stream.last_granule as u64).saturating_sub(stream.pre_skip);
+            let seconds = samples as f64 / stream.rate as f64;
+            duration = Some(duration.map_or(seconds, |d| d.max(seconds)));
+        }
+    }
+    if let Some(seconds) = duration {
+        details.push(("Duration", format_duration(seconds)));
+    }
+    Some(details)
+}
+
+/// Reads an EBML variable length integer; `keep_marker` retains the length bit, as element IDs do.
+fn ebml_vint(data: &[u8], pos: usize, keep_marker: bool) -> Option<(u64, usize)> {
+    let first = *data.get(pos)?;
+    let length = first.leading_zeros() as usize + 1;
+    if length > 8 {
+        return None;
+    }
+    let mut value = if keep_marker { first as u64 } else { (first as u64) & (0xFF >> length) };
+    for i in 1..length {
+        value = (value << 8) | *data.get(pos + i)? as u64;
+    }
+    Some((value, length))
+}
+
+const EBML_DOC_TYPE: u64 = 0x4282;
+const MKV_SEGMENT: u64 = 0x18538067;
+const MKV_INFO: u64 = 0x1549A966;
+const MKV_TIMECODE_SCALE: u64 = 0x2AD7B1;
+const MKV_DURATION: u64 = 0x4489;
+const MKV_TRACKS: u64 = 0x1654AE6B;
+const MKV_TRACK_ENTRY: u64 = 0xAE;
+const MKV_TRACK_TYPE: u64 = 0x83;
+const MKV_CODEC_ID: u64 = 0x86;
+const MKV_VIDEO: u64 = 0xE0;
+const MKV_PIXEL_WIDTH: u64 = 0xB0;
+const MKV_PIXEL_HEIGHT: u64 = 0xBA;
+const MKV_AUDIO: u64 = 0xE1;
+const MKV_SAMPLING_FREQUENCY: u64 = 0xB5;
+const MKV_CHANNELS: u64 = 0x9F;
+const MKV_CLUSTER: u64 = 0x1F43B675;
+
+/// Iterates over the EBML elements in `data` as (id, body).
+///
+/// Elements of unknown or overlong size extend to the end of `data`.
+fn ebml_elements(data: &[u8]) -> impl Iterator<Item = (u64, &[u8])> {
+    let mut pos = 0;
+    std::iter::from_fn(move || {
+        let (id, id_length) = ebml_vint(data, pos, true)?;
+        let (size, size_length) = ebml_vint(data, pos + id_length, false)?;
+        let start = pos + id_length + size_length;
+        let end = usize::try_from(size).ok().and_then(|size| start.checked_add(size)).unwrap_or(usize::MAX).min(data.len());
+        pos = end;
+        Some((id, data.get(start..end)?))
+    })
+}
+
+fn ebml_uint(body: &[u8]) -> u64 {
+    body.iter().fold(0, |acc, &b| (acc << 8) | b as u64)
+}
+
+fn ebml_float(body: &[u8]) -> Option<f64> {
+    match body.len() {
+        4 => Some(f32::from_be_bytes(body.try_into().ok()?) as f64),
+        8 => Some(f64::from_be_bytes(body.try_into().ok()?)),
+        _ => None,
+    }
+}
+
+fn matroska_track(entry: &[u8]) -> String {
+    let mut kind = "other";
+    let mut codec = String::new();
+    let mut extra = String::new();
+    for (id, body) in ebml_elements(entry) {
+        match id {
+            MKV_TRACK_TYPE => {
+                kind = match ebml_uint(body) {
+                    1 => "video",
+                    2 => "audio",
+                    17 => "subtitle",
+                    _ => "other",
+                }
+            }
+            MKV_CODEC_ID => codec = String::from_utf8_lossy(body).trim_end_matches('\0').to_string(),
+            MKV_VIDEO => {
+                let (mut width, mut height) = (0, 0);
+                for (id, body) in ebml_elements(body) {
+                    match id {
+                        MKV_PIXEL_WIDTH => width = ebml_uint(body),
+                        MKV_PIXEL_HEIGHT => height = ebml_uint(body),
+                        _ => {}
+                    }
+                }
+                extra = format!(", {}x{}", width, height);
+            }
+            MKV_AUDIO => {
+                let (mut rate, mut channels) = (8000.0, 1);
+                for (id, body) in ebml_elements(body) {
+                    match id {
+                        MKV_SAMPLING_FREQUENCY => rate = ebml_float(body).unwrap_or(rate),
+                        MKV_CHANNELS => channels = ebml_uint(body),
+                        _ => {}
+                    }
+                }
+                extra = format!(", {}, {} channels", rate_text(rate), channels);
+            }
+            _ => {}
+        }
+    }
+    format!("{} {}{}", kind, codec, extra)
+}
+
+fn matroska_details(head: &[u8]) -> Option<Details> {
+    let mut details = Vec::new();
+    let mut streams = Vec::new();
+
+    for (id, body) in ebml_elements(head) {
+        match id {
+            0x1A45DFA3 => {
+                if let Some((_, doc_type)) = ebml_elements(body).find(|(id, _)| *id == EBML_DOC_TYPE) {
+                    details.push(("Container", String::from_utf8_lossy(doc_type).trim_end_matches('\0').to_string()));
+                }
+            }
+            MKV_SEGMENT => {
+                for (id, body) in ebml_elements(body) {
+                    match id {
+                        MKV_INFO => {
+                            let mut scale = 1_000_000.0;
+                            let mut duration = None;
+                            for (id, body) in ebml_elements(body) {
+                                match id {
+                                    MKV_TIMECODE_SCALE => scale = ebml_uint(body) as f64,
+                                    MKV_DURATION => duration = ebml_float(body),
+                                    _ => {}
+                                }
+                            }
+                            if let Some(duration) = duration {
+                                details.push(("Duration", format_duration(duration * scale / 1e9)));
+                            }
+                        }
+                        MKV_TRACKS => {
+                            streams.extend(
+                                ebml_elements(body)
+                                    .filter(|(id, _)| *id == MKV_TRACK_ENTRY)
+                                    .map(|(_, entry)| matroska_track(entry)),
+                            );
+                        }
+                        MKV_CLUSTER => break,
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if details.is_empty() && streams.is_empty() {
+        return None;
+    }
+    details.push(("Streams", streams.len().to_string()));
+    details.extend(streams.into_iter().map(|stream| ("Stream", stream)));
+    Some(details)
+}
+
+/// The fields of an MPEG audio frame header needed for timing.
+struct MpegFrame {
+    version: &'static str,
+    layer: u8,
+    bitrate: u32,
+    rate: u32,
+    channels: u8,
+    samples: u32,
+    side_info: usize,
+    /// Bytes up to the next frame's header.
+    length: usize,
+}
+
+fn mpeg_frame(data: &[u8], pos: usize) -> Option<MpegFrame> {
+    let header = u32_be(data, pos)?;
+    if header >> 21 != 0x7FF {
+        return None;
+    }
+    let version_bits = (header >> 19) & 0x03;
+    let layer_bits = (header >> 17) & 0x03;
+    let bitrate_index = ((header >> 12) & 0x0F) as usize;
+    let rate_index = ((header >> 10) & 0x03) as usize;
+    if version_bits == 1 || layer_bits == 0 || bitrate_index == 0 || bitrate_index == 15 || rate_index == 3 {
+        return None;
+    }
+    let layer = 4 - layer_bits as u8;
+    let mpeg1 = version_bits == 3;
+
+    const BITRATES_V1: [[u32; 15]; 3] = [
+        [0, 32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448],
+        [0, 32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384],
+        [0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320],
+    ];
+    const BITRATES_V2: [[u32; 15]; 2] = [
+        [0, 32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256],
+        [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160],
+    ];
+    let bitrate = if mpeg1 {
+        BITRATES_V1[layer as usize - 1][bitrate_index]
+    } else {
+        BITRATES_V2[(layer as usize - 1).min(1)][bitrate_index]
+    };
+    let base_rate = [44_100, 48_000, 32_000][rate_index];
+    let (version, rate) = match version_bits {
+        3 => ("MPEG-1", base_rate),
+        2 => ("MPEG-2", base_rate / 2),
+        _ => ("MPEG-2.5", base_rate / 4),
+    };
+    let channels = if (header >> 6) & 0x03 == 3 { 1 } else { 2 };
+    let samples = match (layer, mpeg1) {
+        (1, _) => 384,
+        (2, _) | (3, true) => 1152,
+        _ => 576,
+    };
+    let side_info = match (mpeg1, channels) {
+        (true, 1) | (false, 2) => 17,
+        (true, _) => 32,
+        (false, _) => 9,
+    };
+    // Layer I counts in four-byte slots, the other layers in bytes.
+    let padding = (header >> 9) & 0x01;
+    let length = if layer == 1 {
+        (12 * bitrate * 1000 / rate + padding) * 4
+    } else {
+        samples / 8 * bitrate * 1000 / rate + padding
+    } as usize;
+    Some(MpegFrame { version, layer, bitrate: bitrate * 1000, rate, channels, samples, side_info, length })
+}
+
+fn mp3_details(head: &[u8], file_size: u64) -> Option<Details> {
+    let mut details = Vec::new();
+    let mut pos = 0;
+    if head.starts_with(b"ID3") {
+        let size = head.get(6..10)?.iter().fold(0usize, |acc, &b| (acc << 7) | (b & 0x7F) as usize);
+        let footer = if head.get(5)? & 0x10 != 0 { 10 } else { 0 };
+        details.push(("Tag", format!("ID3v2.{}.{}", head.get(3)?, head.get(4)?)));
+        pos = 10 + size + footer;
+    }
+
+    // Skip padding or junk up to the first frame that is followed by another of the same stream.
+    let audio = (pos..head.len().saturating_sub(4)).find_map(|p| {
+        let frame = mpeg_frame(head, p)?;
+        let next = mpeg_frame(head, p + frame.length)?;
+        (next.version == frame.version && next.layer == frame.layer && next.rate == frame.rate).then_some((p, frame))
+    });
+    let Some((audio_start, frame)) = audio else {
+        return (!details.is_empty()).then_some(details);
+    };
+
+    details.push(("Codec", format!("{} Layer {}", frame.version, "I".repeat(frame.layer as usize))));
+    details.push(("Sample Rate", rate_text(frame.rate)));
+    details.push(("Channels", frame.channels.to_string()));
+
+    // A Xing/Info or VBRI header in the first frame carries the real frame count.
+    let xing = audio_start + 4 + frame.side_info;
+    let frames = match head.get(xing..xing + 4) {
+        Some(b"Xing") | Some(b"Info") if u32_be(head, xing + 4).is_some_and(|flags| flags & 0x01 != 0) => u32_be(head, xing + 8),
+        _ if head.get(audio_start + 36..audio_start + 40) == Some(b"VBRI") => u32_be(head, audio_start + 50),
+        _ => None,
+    };
+    let duration = match frames {
+        Some(frames) => {
+            details.push(("Bitrate", "variable".to_string()));
+            frames as f64 * frame.samples as f64 / frame.rate as f64
+        }
+        None => {
+            details.push(("Bitrate", format!("{} kbit/s", frame.bitrate / 1000)));
+            file_size.saturating_sub(audio_start as u64) as f64 * 8.0 / frame.bitrate as f64
+        }
+    };
+    details.push(("Duration", format_duration(duration)));
+    Some(details)
+}
+
+fn midi_varlen(data: &[u8], pos: &mut usize) -> Option<u64> {
+    let mut value = 0u64;
+    for _ in 0..4 {
+        let b = *data.get(*pos)?;
+        *pos += 1;
+        value = (value << 7) | (b & 0x7F) as u64;
+        if b & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// Walks a track's events, returning its length in ticks and any tempo changes.
+fn midi_track(track: &[u8], tempos: &mut Vec<(u64, u32)>) -> Option<u64> {
+    let mut pos = 0;
+    let mut tick = 0;
+    let mut running_status = 0;
+    while pos < track.len() {
+        tick += midi_varlen(track, &mut pos)?;
+        let mut status = *track.get(pos)?;
+        if status & 0x80 != 0 {
+            pos += 1;
+        } else {
+            status = running_status;
+        }
+        match status {
+            0xFF => {
+                let kind = *track.get(pos)?;
+                pos += 1;
+                let length = midi_varlen(track, &mut pos)? as usize;
+                if kind == 0x51 && length == 3 {
+                    let data = track.get(pos..pos + 3)?;
+                    tempos.push((tick, (data[0] as u32) << 16 | (data[1] as u32) << 8 | data[2] as u32));
+                }
+                pos += length;
+                if kind == 0x2F {
+                    break;
+                }
+            }
+            0xF0 | 0xF7 => pos += midi_varlen(track, &mut pos)? as usize,
+            0x80..=0xEF => {
+                running_status = status;
+                pos += if matches!(status & 0xF0, 0xC0 | 0xD0) { 1 } else { 2 };
+            }
+            _ => return None,
+        }
+    }
+    Some(tick)
+}
+
+fn midi_details(data: &[u8]) -> Option<Details> {
+    let format = u16_be(data, 8)?;
+    let track_count = u16_be(data, 10)?;
+    let division = u16_be(data, 12)?;
+    let mut details = vec![
+        ("Codec", "Standard MIDI".to_string()),
+        ("Format", format.to_string()),
+        ("Tracks", track_count.to_string()),
+    ];
+
+    let mut tempos = Vec::new();
+    let mut end_tick = 0;
+    let mut pos = 8 + u32_be(data, 4)? as usize;
+    while let (Some(kind), Some(length)) = (data.get(pos..pos + 4), u32_be(data, pos + 4)) {
+        let body = data.get(pos + 8..(pos + 8 + length as usize).min(data.len()))?;
+        if kind == b"MTrk" {
+            end_tick = end_tick.max(midi_track(body, &mut tempos)?);
+        }
+        pos += 8 + length as usize;
+    }
+
+    let seconds = if division & 0x8000 != 0 {
+        // SMPTE timing: frames per second times ticks per frame.
+        let fps = match (division >> 8) as u8 as i8 {
+            -29 => 29.97,
+            fps => -(fps as f64),
+        };
+        end_tick as f64 / (fps * (division & 0xFF) as f64)
+    } else {
+        tempos.sort_by_key(|&(tick, _)| tick);
+        let ticks_per_quarter = division as f64;
+        let (mut seconds, mut last_tick, mut tempo) = (0.0, 0, 500_000);
+        for (tick, new_tempo) in tempos.into_iter().filter(|&(tick, _)| tick <= end_tick) {
+            seconds += (tick - last_tick) as f64 * tempo as f64 / ticks_per_quarter / 1e6;
+            last_tick = tick;
+            tempo = new_tempo;
+        }
+        seconds + (end_tick - last_tick) as f64 * tempo as f64 / ticks_per_quarter / 1e6
+    };
+    details.push(("Duration", format_duration(seconds)));
+    Some(details)
+}
+
+/// Reports codec, sample rate, channels, duration and streams of audio and video files.
+pub fn inspect(source: &Source) -> io::Result<Details> {
+    let head = source.range(0, HEAD_SIZE)?;
+    let details = if head.starts_with(b"fLaC") {
+        flac_details(&head)
+    } else if head.starts_with(b"RIFF") && is_wave(&head) {
+        wave_details(source)?
+    } else if head.starts_with(b"RIFF") && is_avi(&head) {
+        avi_details(&head)
+    } else if head.starts_with(b"OggS") {
+        let size = source.size();
+        let tail_start = size.saturating_sub(OGG_TAIL_SIZE as u64);
+        ogg_details(&head, &source.range(tail_start, OGG_TAIL_SIZE)?)
+    } else if head.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        matroska_details(&head)
+    } else if head.starts_with(b"MThd") {
+        midi_details(&source.range(0, MAX_MIDI_SIZE)?)
+    } else if head.starts_with(b"ID3") || head.first() == Some(&0xFF) {
+        mp3_details(&head, source.size())
+    } else {
+        None
+    };
+    Ok(details.unwrap_or_else(|| vec![("Parse Error", "truncated or malformed media header".to_string())]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inspect_bytes(name: &str, data: &[u8]) -> Details {
+        inspect(&Source::from_bytes(name, data)).unwrap()
+    }
+
+    fn expected(pairs: &[(&'static str, &str)]) -> Details {
+        pairs.iter().map(|&(key, value)| (key, value.to_string())).collect()
+    }
+
+    #[test]
+    fn reports_pcm_wave() {
+        // One second of 16-bit stereo at 44.1 kHz; the data itself is not needed.
+        let mut wave = b"RIFF\0\0\0\0WAVEfmt \x10\0\0\0".to_vec();
+        wave.extend([1, 0, 2, 0]);
+        wave.extend(44_100u32.to_le_bytes());
+        wave.extend(176_400u32.to_le_bytes());
+        wave.extend([4, 0, 16, 0]);
+        wave.extend(b"data");
+        wave.extend(176_400u32.to_le_bytes());
+        assert_eq!(
+            inspect_bytes("audio.wav", &wave),
+            expected(&[
+                ("Codec", "PCM"),
+                ("Sample Rate", "44100 Hz"),
+                ("Channels", "2"),
+                ("Bits Per Sample", "16"),
+                ("Duration", "00:00:01.000 (1.00 s)"),
+            ])
+        );
+    }
+
+    #[test]
+    fn reports_constant_bitrate_mp3() {
+        // Two MPEG-1 Layer III frames, 128 kbit/s at 44.1 kHz, 417 bytes each.
+        let frame = [&[0xFF, 0xFB, 0x90, 0x00][..], &[0; 413]].concat();
+        let mp3 = frame.repeat(2);
+        assert_eq!(
+            inspect_bytes("audio.mp3", &mp3),
+            expected(&[
+                ("Codec", "MPEG-1 Layer III"),
+                ("Sample Rate", "44100 Hz"),
+                ("Channels", "2"),
+                ("Bitrate", "128 kbit/s"),
+                ("Duration", "00:00:00.052 (0.05 s)"),
+            ])
+        );
+    }
+
+    #[test]
+    fn truncated_media_reports_parse_error() {
+        let flac = b"fLaC\0\0\0\x22\x10\0\x10\0";
+        let wave = b"RIFF\x04\0\0\0WAVE";
+        let mp3 = [0xFF, 0xFB, 0x90, 0x00, 0, 0, 0, 0];
+        for (name, data) in [("truncated.flac", &flac[..]), ("empty.wav", &wave[..]), ("single-frame.mp3", &mp3[..])] {
+            assert_eq!(inspect_bytes(name, data)[0].0, "Parse Error", "{}", name);
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! `magicheck organize`: copies or moves files into folders by detected category.
+
+use std::collections::HashSet;
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+
+use crate::cli::OrganizeOptions;
+use crate::signatures::{self, Signature, UNKNOWN_MIME};
+use crate::{identify_file, mime_matches, transfer, walk};
+
+/// Category folders and the MIME types filed under them; the first match wins.
+const CATEGORIES: &[(&str, &[&str])] = &[
+    ("images", &["image/"]),
+    ("media", &["audio/", "video/", "application/ogg"]),
+    (
+        "documents",
+        &[
+            "application/pdf",
+            "application/rtf",
+            "application/postscript",
+            "application/x-ole-storage",
+            "application/vnd.ms-outlook",
+            "application/vnd.lotus-1-2-3",
+            "application/x-msaccess",
+            "application/vnd.quark.quarkxpress",
+            "application/x-indesign",
+            "application/x-clarisworks",
+            "application/x-appleworks",
+            "application/winhlp",
+            "application/vnd.ms-htmlhelp",
+            "application/xml",
+            "message/rfc822",
+            "text/plain",
+        ],
+    ),
+    (
+        "archives",
+        &[
+            "application/zip",
+            "application/gzip",
+            "application/zlib",
+            "application/zstd",
+            "application/x-bzip",
+            "application/x-xz",
+            "application/x-lz",
+            "application/x-7z-compressed",
+            "application/vnd.rar",
+            "application/x-compress",
+            "application/x-cpio",
+            "application/x-arj",
+            "application/x-arc",
+            "application/x-freearc",
+            "application/x-zoo",
+            "application/x-xar",
+            "application/x-ms-wim",
+            "application/vnd.ms-cab-compressed",
+            "application/x-rpm",
+            "application/vnd.debian.binary-package",
+            "application/x-chrome-extension",
+            "application/x-iso9660-image",
+            "application/x-compressed-iso",
+        ],
+    ),
+    (
+        "executables",
+        &[
+            "application/x-executable",
+            "application/x-dosexec",
+            "application/vnd.microsoft.portable-executable",
+            "application/x-mach-binary",
+            "application/x-amiga-executable",
+            "application/java-vm",
+            "application/vnd.android.dex",
+            "application/wasm",
+            "application/x-lua-bytecode",
+            "text/x-shellscript",
+        ],
+    ),
+];
+/// Folder for files outside every category, including unknown ones.
+const OTHER_CATEGORY: &str = "other";
+
+fn category(signature: Option<&Signature>) -> &'static str {
+    let mime = signature.map_or(UNKNOWN_MIME, |s| s.mime);
+    CATEGORIES
+        .iter()
+        .find(|(_, patterns)| patterns.iter().any(|pattern| mime_matches(mime, pattern)))
+        .map_or(OTHER_CATEGORY, |(name, _)| name)
+}
+
+/// Types whose extensions say little about a file: containers and generic
+/// formats carry many formats under their own names (EPUB and wheels are
+/// ZIP archives, SVG is XML, any script starts with a shebang).
+const GENERIC_MIMES: &[&str] = &[
+    "application/zip",
+    "application/gzip",
+    "application/zlib",
+    "application/xml",
+    "application/x-ole-storage",
+    "text/plain",
+    "text/x-shellscript",
+];
+
+/// Whether `extension` is one of another type's, so the file is misnamed.
+fn belongs_elsewhere(extension: &str, signature: &Signature) -> bool {
+    let claims = |s: &Signature| s.extensions.iter().any(|ext| ext.eq_ignore_ascii_case(extension));
+    !claims(signature) && signatures::all().any(|other| other.mime != signature.mime && claims(other))
+}
+
+/// `file_name` given the type's usual extension when it has none, or when
+/// its extension is another type's. Files of generic types keep their names.
+fn fixed_name(file_name: &OsStr, signature: Option<&Signature>) -> OsString {
+    let Some(signature) = signature.filter(|s| !GENERIC_MIMES.contains(&s.mime)) else {
+        return file_name.to_os_string();
+    };
+    let Some(usual) = signature.extensions.first() else {
+        return file_name.to_os_string();
+    };
+    let path = Path::new(file_name);
+    let mut name = match path.extension() {
+        None => file_name.to_os_string(),
+        Some(current) if current.to_str().is_some_and(|current| belongs_elsewhere(current, signature)) => {
+            path.file_stem().unwrap_or(file_name).to_os_string()
+        }
+        Some(_) => return file_name.to_os_string(),
+    };
+    name.push(".");
+    name.push(usual);
+    name
+}
+
+/// Predicts where a file will land, skipping names already taken on disk or
+/// earlier in the plan.
+fn planned_destination(dir: &Path, file_name: &OsStr, planned: &mut HashSet<PathBuf>) -> PathBuf {
+    transfer::candidates(file_name)
+        .map(|name| dir.join(name))
+        .find(|destination| !destination.exists() && !planned.contains(destination))
+        .inspect(|destination| {
+            planned.insert(destination.clone());
+        })
+        .expect("candidate names are unbounded")
+}
+
+/// Files everything under `options.source` into category folders below
+/// `options.destination`, printing one line per file. Returns whether every
+/// file was handled.
+pub fn run(options: &OrganizeOptions) -> bool {
+    let mut succeeded = true;
+    let file_paths = walk::collect_files(std::slice::from_ref(&options.source), true, &mut |path, e| {
+        eprintln!("Error reading directory '{}': {}", path.display(), e);
+        succeeded = false;
+    });
+
+    let verb = if options.move_files { "move" } else { "copy" };
+    let mut planned = HashSet::new();
+    for file_path in &file_paths {
+        let detection = match identify_file(file_path) {
+            Ok(detection) => detection,
+            Err(e) => {
+                eprintln!("Error processing file '{}': {}", file_path.display(), e);
+                succeeded = false;
+                continue;
+            }
+        };
+        let Some(file_name) = file_path.file_name() else {
+            continue;
+        };
+        let file_name = if options.fix_extensions {
+            fixed_name(file_name, detection.signature)
+        } else {
+            file_name.to_os_string()
+        };
+        let dir = Path::new(&options.destination).join(category(detection.signature));
+
+        let destination = if options.dry_run {
+            Ok(planned_destination(&dir, &file_name, &mut planned))
+        } else if options.move_files {
+            transfer::move_into(file_path, &dir, &file_name)
+        } else {
+            transfer::copy_into(file_path, &dir, &file_name)
+        };
+        match destination {
+            Ok(destination) => println!("{} {} -> {}", verb, file_path.display(), destination.display()),
+            Err(e) => {
+                eprintln!("Error organizing file '{}': {}", file_path.display(), e);
+                succeeded = false;
+            }
+        }
+    }
+    succeeded
+}
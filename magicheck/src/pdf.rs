@@ -0,0 +1,189 @@
+use std::io::{self, Read};
+
+use flate2::read::ZlibDecoder;
+
+use crate::reader::Source;
+use crate::signatures::Details;
+
+/// Larger documents are only scanned up to this many bytes.
+const MAX_SCAN_SIZE: usize = 64 * 1024 * 1024;
+/// A linearization dictionary must be the first object in the file.
+const LINEARIZED_WINDOW: usize = 1024;
+/// Upper bound on a single inflated object stream, guarding against decompression bombs.
+const MAX_INFLATED_SIZE: u64 = 16 * 1024 * 1024;
+
+fn is_delimiter(byte: u8) -> bool {
+    byte.is_ascii_whitespace() || b"()<>[]{}/%\0".contains(&byte)
+}
+
+/// Iterates over the PDF names in `data` with `#xx` escapes decoded, so
+/// that obfuscated spellings such as `/J#61vaScript` are still recognised.
+fn names(data: &[u8]) -> impl Iterator<Item = (usize, Vec<u8>)> + '_ {
+    let mut pos = 0;
+    std::iter::from_fn(move || {
+        let start = pos + data.get(pos..)?.iter().position(|&b| b == b'/')?;
+        let mut name = Vec::new();
+        let mut i = start + 1;
+        while let Some(&byte) = data.get(i).filter(|&&b| !is_delimiter(b)) {
+            let escaped = data
+                .get(i + 1..i + 3)
+                .filter(|_| byte == b'#')
+                .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+            match escaped {
+                Some(decoded) => {
+                    name.push(decoded);
+                    i += 3;
+                }
+                None => {
+                    name.push(byte);
+                    i += 1;
+                }
+            }
+        }
+        pos = i;
+        Some((start, name))
+    })
+}
+
+/// Counts of the names of interest across the file and its object streams.
+#[derive(Default)]
+struct Scan {
+    encrypted: bool,
+    linearized: bool,
+    pages: usize,
+    javascript: usize,
+    embedded_files: usize,
+    object_streams: Vec<usize>,
+}
+
+impl Scan {
+    fn names(&mut self, data: &[u8], top_level: bool) {
+        let mut previous: Option<Vec<u8>> = None;
+        for (offset, name) in names(data) {
+            match name.as_slice() {
+                b"Encrypt" => self.encrypted = true,
+                b"Linearized" if top_level && offset < LINEARIZED_WINDOW => self.linearized = true,
+                b"ObjStm" if top_level => self.object_streams.push(offset),
+                b"JavaScript" | b"JS" => self.javascript += 1,
+                b"EmbeddedFile" | b"EmbeddedFiles" => self.embedded_files += 1,
+                b"Page" if previous.as_deref() == Some(b"Type") => self.pages += 1,
+                _ => {}
+            }
+            previous = Some(name);
+        }
+    }
+}
+
+fn find(data: &[u8], from: usize, needle: &[u8]) -> Option<usize> {
+    Some(from + data.get(from..)?.windows(needle.len()).position(|w| w == needle)?)
+}
+
+/// Inflates the first stream following `offset`, which PDF 1.5+ uses to hold compressed objects.
+fn inflate_stream(data: &[u8], offset: usize) -> Option<Vec<u8>> {
+    let mut start = find(data, offset, b"stream")? + b"stream".len();
+    start += data[start..].iter().take(2).take_while(|b| matches!(b, b'\r' | b'\n')).count();
+    let end = find(data, start, b"endstream")?;
+    let mut inflated = Vec::new();
+    ZlibDecoder::new(&data[start..end]).take(MAX_INFLATED_SIZE).read_to_end(&mut inflated).ok()?;
+    Some(inflated)
+}
+
+fn yes_no(flag: bool) -> String {
+    if flag { "yes" } else { "no" }.to_string()
+}
+
+fn references(count: usize) -> String {
+    if count > 0 { format!("yes ({} references)", count) } else { yes_no(false) }
+}
+
+fn pdf_details(data: &[u8], complete: bool) -> Details {
+    let version = data
+        .get(5..)
+        .map(|rest| rest.iter().take_while(|b| b.is_ascii_digit() || **b == b'.').map(|&b| b as char).collect::<String>())
+        .filter(|version| !version.is_empty())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let mut scan = Scan::default();
+    scan.names(data, true);
+    let mut unreadable_streams = 0;
+    for offset in std::mem::take(&mut scan.object_streams) {
+        match inflate_stream(data, offset) {
+            Some(objects) => scan.names(&objects, false),
+            None => unreadable_streams += 1,
+        }
+    }
+
+    let mut pages = scan.pages.to_string();
+    if unreadable_streams > 0 {
+        pages.push_str(&format!(" (at least; {} object streams unreadable)", unreadable_streams));
+    }
+    if !complete {
+        pages.push_str(" (scan incomplete)");
+    }
+
+    vec![
+        ("Version", version),
+        ("Encrypted", yes_no(scan.encrypted)),
+        ("Linearized", yes_no(scan.linearized)),
+        ("Pages", pages),
+        ("JavaScript", references(scan.javascript)),
+        ("Embedded Files", references(scan.embedded_files)),
+    ]
+}
+
+/// Summarises a PDF's version, encryption, linearization, page count and active content.
+pub fn inspect(source: &Source) -> io::Result<Details> {
+    let data = source.range(0, MAX_SCAN_SIZE)?;
+    let complete = source.size() <= MAX_SCAN_SIZE as u64;
+    Ok(pdf_details(&data, complete))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+
+    use super::*;
+
+    fn inspect_bytes(name: &str, data: &[u8]) -> Details {
+        inspect(&Source::from_bytes(name, data)).unwrap()
+    }
+
+    fn document(object_stream: &[u8]) -> Vec<u8> {
+        let mut pdf = b"%PDF-1.7\n1 0 obj << /Linearized 1 >> endobj\n2 0 obj << /Type /Page >> endobj\n".to_vec();
+        pdf.extend(b"3 0 obj << /S /J#61vaScript >> endobj\n4 0 obj << /Type /ObjStm >>\nstream\n");
+        pdf.extend(object_stream);
+        pdf.extend(b"\nendstream\nendobj\n%%EOF\n");
+        pdf
+    }
+
+    #[test]
+    fn reports_names_inside_object_streams() {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(b"<< /Type /Page >> << /Type /EmbeddedFile >>").unwrap();
+        let details = inspect_bytes("document.pdf", &document(&encoder.finish().unwrap()));
+        let expected = [
+            ("Version", "1.7"),
+            ("Encrypted", "no"),
+            ("Linearized", "yes"),
+            ("Pages", "2"),
+            ("JavaScript", "yes (1 references)"),
+            ("Embedded Files", "yes (1 references)"),
+        ];
+        assert_eq!(details, expected.map(|(key, value)| (key, value.to_string())));
+    }
+
+    #[test]
+    fn corrupt_object_stream_marks_page_count_as_partial() {
+        // PDF scanning has no parse error: what is found is reported, and a
+        // stream that does not inflate makes the page count a lower bound.
+        let details = inspect_bytes("corrupt.pdf", &document(b"not zlib data"));
+        assert_eq!(details[3], ("Pages", "1 (at least; 1 object streams unreadable)".to_string()));
+
+        let details = inspect_bytes("truncated.pdf", b"%PDF-");
+        assert_eq!(details[0], ("Version", "unknown".to_string()));
+        assert_eq!(details[3], ("Pages", "0".to_string()));
+    }
+}
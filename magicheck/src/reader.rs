@@ -0,0 +1,125 @@
+//! Reads files through a memory map where possible: the head for
+//! identification, and any range the inspectors ask for.
+
+use std::borrow::Cow;
+use std::fs::{File, Metadata};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::ops::Deref;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+/// The leading bytes of a file, either mapped or copied into memory.
+pub enum Chunk {
+    Mapped { map: Mmap, length: usize },
+    Buffered(Vec<u8>),
+}
+
+impl Deref for Chunk {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Chunk::Mapped { map, length } => &map[..*length],
+            Chunk::Buffered(buffer) => buffer,
+        }
+    }
+}
+
+impl Chunk {
+    /// The last `length` bytes of the file, when it is mapped; streams
+    /// cannot be read from the end.
+    pub fn tail(&self, length: usize) -> Option<&[u8]> {
+        match self {
+            Chunk::Mapped { map, .. } => Some(&map[map.len().saturating_sub(length)..]),
+            Chunk::Buffered(_) => None,
+        }
+    }
+}
+
+/// Maps `file` when it is a regular file with content. Pipes, devices and
+/// files that report no size (such as those under /proc) cannot be mapped.
+fn map(file: &File, metadata: &Metadata) -> Option<Mmap> {
+    if !metadata.is_file() || metadata.len() == 0 {
+        return None;
+    }
+    // SAFETY: the map is read-only and dropped once the file is examined.
+    // A file truncated by another process meanwhile can still fault; that
+    // is the accepted cost of mapping.
+    unsafe { Mmap::map(file) }.ok()
+}
+
+/// Reads up to `length` leading bytes of `file_path`. Regular files are
+/// mapped so only the pages actually tested are read; other files are read
+/// into a buffer.
+///
+/// When `sink` is given the whole file is also written to it in the same
+/// pass, so a stream is still consumed only once.
+pub fn read_chunk(file_path: &Path, length: usize, sink: Option<&mut dyn Write>) -> io::Result<Chunk> {
+    let mut file = File::open(file_path)?;
+    if let Some(map) = map(&file, &file.metadata()?) {
+        if let Some(sink) = sink {
+            sink.write_all(&map)?;
+        }
+        let length = length.min(map.len());
+        return Ok(Chunk::Mapped { map, length });
+    }
+    // Left to grow with what is read: `length` covers the deepest signature, not this file
+    let mut buffer = Vec::new();
+    Read::by_ref(&mut file).take(length as u64).read_to_end(&mut buffer)?;
+    if let Some(sink) = sink {
+        sink.write_all(&buffer)?;
+        io::copy(&mut file, sink)?;
+    }
+    Ok(Chunk::Buffered(buffer))
+}
+
+/// A file opened for inspection. Regular files are mapped once, so parsers
+/// can read any range without further system calls; other files are read
+/// with a seek per range.
+pub struct Source {
+    file: File,
+    map: Option<Mmap>,
+    size: u64,
+}
+
+impl Source {
+    pub fn open(file_path: &Path) -> io::Result<Source> {
+        let file = File::open(file_path)?;
+        let metadata = file.metadata()?;
+        let map = map(&file, &metadata);
+        Ok(Source { file, map, size: metadata.len() })
+    }
+
+    /// The file's size as its metadata reports it.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Up to `length` bytes starting at `offset`; shorter at end of file.
+    pub fn range(&self, offset: u64, length: usize) -> io::Result<Cow<'_, [u8]>> {
+        if let Some(map) = &self.map {
+            let start = usize::try_from(offset).map_or(map.len(), |offset| offset.min(map.len()));
+            let end = start.saturating_add(length).min(map.len());
+            return Ok(Cow::Borrowed(&map[start..end]));
+        }
+        let mut file = &self.file;
+        file.seek(SeekFrom::Start(offset))?;
+        let mut buffer = Vec::new();
+        file.take(length as u64).read_to_end(&mut buffer)?;
+        Ok(Cow::Owned(buffer))
+    }
+}
+
+#[cfg(test)]
+impl Source {
+    /// A source over `data`, through a temporary file named after `name`
+    /// that is removed once opened.
+    pub fn from_bytes(name: &str, data: &[u8]) -> Source {
+        let path = std::env::temp_dir().join(format!("magicheck-{}-{}", std::process::id(), name));
+        std::fs::write(&path, data).unwrap();
+        let source = Source::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        source
+    }
+}
@@ -0,0 +1,66 @@
+use std::fs;
+use std::io;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+use crate::signatures::Signature;
+
+/// What kind of sensitive material a signature indicates.
+#[derive(Clone, Copy, PartialEq)]
+pub enum SecretKind {
+    PrivateKey,
+    KeyStore,
+    PasswordDatabase,
+    Wallet,
+}
+
+impl SecretKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            SecretKind::PrivateKey => "private key",
+            SecretKind::KeyStore => "key store",
+            SecretKind::PasswordDatabase => "password database",
+            SecretKind::Wallet => "wallet",
+        }
+    }
+}
+
+/// A file holding key material or credentials, with any permission problems.
+pub struct Finding {
+    pub kind: SecretKind,
+    pub mode: u32,
+    pub problems: Vec<&'static str>,
+}
+
+impl Finding {
+    /// Whether the permissions expose the secret to other users.
+    pub fn is_exposed(&self) -> bool {
+        !self.problems.is_empty()
+    }
+}
+
+/// Checks the permissions of a file whose signature marks it as sensitive.
+///
+/// Returns `Ok(None)` when the signature does not indicate a secret.
+pub fn audit_file(file_path: &Path, signature: Option<&Signature>) -> io::Result<Option<Finding>> {
+    let Some(kind) = signature.and_then(|s| s.secret) else {
+        return Ok(None);
+    };
+
+    let mode = fs::metadata(file_path)?.permissions().mode() & 0o7777;
+    let mut problems = Vec::new();
+    if mode & 0o004 != 0 {
+        problems.push("world-readable");
+    }
+    if mode & 0o002 != 0 {
+        problems.push("world-writable");
+    }
+    if mode & 0o040 != 0 {
+        problems.push("group-readable");
+    }
+    if mode & 0o020 != 0 {
+        problems.push("group-writable");
+    }
+
+    Ok(Some(Finding { kind, mode, problems }))
+}
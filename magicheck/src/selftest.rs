@@ -0,0 +1,140 @@
+//! Built-in self-test: every signature must be detected from a minimal sample.
+
+use std::io::Write;
+
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+
+use crate::signatures::{self, Signature};
+
+/// Bytes around the magic in synthesized samples. A filler can happen to
+/// complete an earlier signature, so an entry passes if any filler works.
+const FILLERS: &[u8] = &[0x00, 0xFF, b' '];
+
+type SampleBuilder = fn() -> Vec<u8>;
+
+/// Samples for signatures whose structural check needs more than the magic.
+const STRUCTURED_SAMPLES: &[(&str, SampleBuilder)] = &[
+    ("Linux ext2/ext3/ext4 filesystem", ext_superblock),
+    ("MBR partitioned disk image", mbr),
+    ("WebP image", || riff(b"WEBP")),
+    ("Waveform Audio File Format", || riff(b"WAVE")),
+    ("Audio Video Interleave video", || riff(b"AVI ")),
+    ("Git loose object", loose_object),
+    // SEQUENCE { SEQUENCE { [0] ... } ... }
+    ("DER encoded X.509 certificate", || vec![0x30, 0x82, 0x01, 0x00, 0x30, 0x81, 0x10, 0xA0, 0x03, 0x02, 0x01, 0x02]),
+];
+
+fn ext_superblock() -> Vec<u8> {
+    let mut sample = vec![0; 0x800];
+    sample[0x404] = 1; // blocks count
+    sample[0x438..0x43A].copy_from_slice(&[0x53, 0xEF]);
+    sample
+}
+
+fn mbr() -> Vec<u8> {
+    let mut sample = vec![0; 0x200];
+    sample[0x1BE + 4] = 0x83; // Linux partition
+    sample[0x1BE + 12] = 1; // one sector long
+    sample[0x1FE..].copy_from_slice(&[0x55, 0xAA]);
+    sample
+}
+
+fn riff(form: &[u8; 4]) -> Vec<u8> {
+    [b"RIFF".as_slice(), &[0; 4], form].concat()
+}
+
+fn loose_object() -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(b"blob 0\0").expect("writing to a Vec cannot fail");
+    encoder.finish().expect("writing to a Vec cannot fail")
+}
+
+/// Minimal files that should be detected as `signature`: its magic at its
+/// offset, padded with each filler, or a hand-built sample when the
+/// signature verifies more than its magic.
+pub fn samples(signature: &Signature) -> Vec<Vec<u8>> {
+    if signature.verify.is_some() {
+        return STRUCTURED_SAMPLES
+            .iter()
+            .filter(|(name, _)| *name == signature.name)
+            .map(|(_, build)| build())
+            .collect();
+    }
+    FILLERS
+        .iter()
+        .map(|&filler| {
+            let mut sample = vec![filler; signature.offset + signature.magic.len()];
+            sample[signature.offset..].copy_from_slice(signature.magic);
+            sample
+        })
+        .collect()
+}
+
+/// A signature that none of its samples is detected as.
+pub struct Failure {
+    /// Position of the signature in `signatures::all()`.
+    pub index: usize,
+    /// Position in `signatures::all()` of whatever each sample was detected as instead.
+    pub detected: Vec<Option<usize>>,
+}
+
+impl Failure {
+    pub fn describe(&self) -> String {
+        let name = |index: usize| {
+            let custom = if index < signatures::custom_count() { "custom " } else { "" };
+            let signature = signatures::all().nth(index).expect("index within signatures::all()");
+            format!("#{} {}{}", index, custom, signature.name)
+        };
+        let problem = if self.detected.is_empty() {
+            "no sample to test with".to_string()
+        } else {
+            let mut found: Vec<String> = self
+                .detected
+                .iter()
+                .map(|detected| detected.map_or_else(|| "nothing".to_string(), name))
+                .collect();
+            found.dedup();
+            format!("sample detected as {}", found.join(", "))
+        };
+        format!("{}: {}", name(self.index), problem)
+    }
+}
+
+/// Checks that every signature tried, custom ones included, is reachable
+/// from at least one sample.
+pub fn check() -> Vec<Failure> {
+    signatures::all()
+        .enumerate()
+        .filter_map(|(index, signature)| {
+            let detected: Vec<Option<usize>> = samples(signature)
+                .iter()
+                .map(|sample| signatures::all().position(|s| s.matches(sample)))
+                .collect();
+            (!detected.contains(&Some(index))).then_some(Failure { index, detected })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signatures::SIGNATURES;
+
+    #[test]
+    fn every_signature_is_reachable() {
+        let failures: Vec<String> = check().iter().map(Failure::describe).collect();
+        assert!(failures.is_empty(), "unreachable signatures:\n{}", failures.join("\n"));
+    }
+
+    #[test]
+    fn structured_samples_name_existing_signatures() {
+        for (name, _) in STRUCTURED_SAMPLES {
+            assert!(
+                SIGNATURES.iter().any(|s| s.name == *name && s.verify.is_some()),
+                "no verified signature named {:?}",
+                name
+            );
+        }
+    }
+}
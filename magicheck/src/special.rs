@@ -0,0 +1,66 @@
+//! Paths that are not regular files with content to identify. They are
+//! recognised from metadata before anything is opened, so a FIFO cannot
+//! block a run waiting for a writer.
+
+use std::fs::Metadata;
+use std::os::unix::fs::FileTypeExt;
+
+/// The kind of a path reported by metadata alone.
+#[derive(Clone, Copy)]
+pub enum Kind {
+    Empty,
+    Directory,
+    BlockDevice,
+    CharacterDevice,
+    Fifo,
+    Socket,
+    BrokenSymlink,
+}
+
+impl Kind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Kind::Empty => "Empty file",
+            Kind::Directory => "Directory",
+            Kind::BlockDevice => "Block device",
+            Kind::CharacterDevice => "Character device",
+            Kind::Fifo => "FIFO (named pipe)",
+            Kind::Socket => "Socket",
+            Kind::BrokenSymlink => "Broken symbolic link",
+        }
+    }
+
+    /// The `inode/*` type shared-mime-info gives these.
+    pub fn mime(self) -> &'static str {
+        match self {
+            Kind::Empty => "inode/x-empty",
+            Kind::Directory => "inode/directory",
+            Kind::BlockDevice => "inode/blockdevice",
+            Kind::CharacterDevice => "inode/chardevice",
+            Kind::Fifo => "inode/fifo",
+            Kind::Socket => "inode/socket",
+            Kind::BrokenSymlink => "inode/symlink",
+        }
+    }
+}
+
+/// The kind of a path that must not be read, from its (followed) metadata.
+/// Block devices are read for disk-image detection when `read_devices` is
+/// set. Empty regular files are not judged here: files under /proc report
+/// no size yet have content, so emptiness is only known after reading.
+pub fn classify(metadata: &Metadata, read_devices: bool) -> Option<Kind> {
+    let file_type = metadata.file_type();
+    if file_type.is_file() {
+        None
+    } else if file_type.is_dir() {
+        Some(Kind::Directory)
+    } else if file_type.is_block_device() {
+        (!read_devices).then_some(Kind::BlockDevice)
+    } else if file_type.is_char_device() {
+        Some(Kind::CharacterDevice)
+    } else if file_type.is_fifo() {
+        Some(Kind::Fifo)
+    } else {
+        Some(Kind::Socket)
+    }
+}
@@ -0,0 +1,141 @@
+use std::io;
+
+use crate::bytes::{u16_be, u32_be};
+use crate::reader::Source;
+use crate::signatures::Details;
+
+const HEADER_SIZE: usize = 100;
+
+/// Registered `PRAGMA application_id` values worth naming.
+const APPLICATION_IDS: &[(u32, &str)] = &[
+    (0x0F05_5112, "Fossil repository"),
+    (0x0F05_5113, "Fossil checkout"),
+    (0x0F05_5111, "Fossil global configuration"),
+    (0x4750_4B47, "GeoPackage"),
+    (0x4750_3130, "GeoPackage 1.0"),
+    (0x4D50_4258, "MBTiles"),
+];
+
+fn sqlite_details(header: &[u8], file_size: u64) -> Option<Details> {
+    if header.len() < HEADER_SIZE {
+        return None;
+    }
+    let page_size = match u16_be(header, 16)? {
+        1 => 65536,
+        size => size as u32,
+    };
+    if !page_size.is_power_of_two() || page_size < 512 {
+        return None;
+    }
+
+    // The in-header page count is only trusted when written by a version that maintains it.
+    let change_counter = u32_be(header, 24)?;
+    let in_header_pages = u32_be(header, 28)?;
+    let page_count = if in_header_pages > 0 && change_counter == u32_be(header, 92)? {
+        in_header_pages as u64
+    } else {
+        file_size / page_size as u64
+    };
+
+    let journal_mode = match (header[18], header[19]) {
+        (2, 2) => "WAL".to_string(),
+        (1, 1) => "rollback journal".to_string(),
+        (write, read) => format!("unknown (write {}, read {})", write, read),
+    };
+    let encoding = match u32_be(header, 56)? {
+        1 => "UTF-8".to_string(),
+        2 => "UTF-16le".to_string(),
+        3 => "UTF-16be".to_string(),
+        other => format!("unknown ({})", other),
+    };
+    let application_id = match u32_be(header, 68)? {
+        0 => "none".to_string(),
+        id => match APPLICATION_IDS.iter().find(|(known, _)| *known == id) {
+            Some((_, name)) => format!("0x{:08x} ({})", id, name),
+            None => format!("0x{:08x}", id),
+        },
+    };
+    let version = u32_be(header, 96)?;
+
+    Some(vec![
+        ("Page Size", page_size.to_string()),
+        ("Page Count", page_count.to_string()),
+        ("Text Encoding", encoding),
+        ("Schema Format", u32_be(header, 44)?.to_string()),
+        ("Journal Mode", journal_mode),
+        ("Application ID", application_id),
+        ("User Version", u32_be(header, 60)?.to_string()),
+        ("SQLite Version", format!("{}.{}.{}", version / 1_000_000, version / 1000 % 1000, version % 1000)),
+    ])
+}
+
+/// Decodes the 100-byte SQLite database header.
+pub fn inspect(source: &Source) -> io::Result<Details> {
+    let header = source.range(0, HEADER_SIZE)?;
+    let file_size = source.size();
+    Ok(sqlite_details(&header, file_size)
+        .unwrap_or_else(|| vec![("Parse Error", "truncated or malformed database header".to_string())]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn put_u32(header: &mut [u8], offset: usize, value: u32) {
+        header[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+    }
+
+    /// A header for a 3-page WAL database written by SQLite 3.45.1.
+    fn header() -> Vec<u8> {
+        let mut header = b"SQLite format 3\0".to_vec();
+        header.resize(HEADER_SIZE, 0);
+        header[16..18].copy_from_slice(&4096u16.to_be_bytes());
+        header[18..20].copy_from_slice(&[2, 2]);
+        put_u32(&mut header, 24, 5);
+        put_u32(&mut header, 28, 3);
+        put_u32(&mut header, 44, 4);
+        put_u32(&mut header, 56, 1);
+        put_u32(&mut header, 60, 7);
+        put_u32(&mut header, 68, 0x4D50_4258);
+        put_u32(&mut header, 92, 5);
+        put_u32(&mut header, 96, 3_045_001);
+        header
+    }
+
+    fn inspect_bytes(name: &str, data: &[u8]) -> Details {
+        inspect(&Source::from_bytes(name, data)).unwrap()
+    }
+
+    #[test]
+    fn reports_database_header() {
+        let expected = [
+            ("Page Size", "4096"),
+            ("Page Count", "3"),
+            ("Text Encoding", "UTF-8"),
+            ("Schema Format", "4"),
+            ("Journal Mode", "WAL"),
+            ("Application ID", "0x4d504258 (MBTiles)"),
+            ("User Version", "7"),
+            ("SQLite Version", "3.45.1"),
+        ];
+        assert_eq!(inspect_bytes("db.sqlite", &header()), expected.map(|(key, value)| (key, value.to_string())));
+    }
+
+    #[test]
+    fn stale_page_count_falls_back_to_file_size() {
+        let mut database = header();
+        put_u32(&mut database, 92, 4);
+        database.resize(2 * 4096, 0);
+        assert_eq!(inspect_bytes("stale.sqlite", &database)[1], ("Page Count", "2".to_string()));
+    }
+
+    #[test]
+    fn malformed_headers_report_parse_error() {
+        let truncated = &header()[..60];
+        let mut bad_page_size = header();
+        bad_page_size[16..18].copy_from_slice(&1000u16.to_be_bytes());
+        for (name, data) in [("truncated.sqlite", truncated), ("page-size.sqlite", &bad_page_size[..])] {
+            assert_eq!(inspect_bytes(name, data)[0].0, "Parse Error", "{}", name);
+        }
+    }
+}
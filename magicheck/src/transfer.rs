@@ -0,0 +1,105 @@
+//! Moving and copying files into destination folders without overwriting anything.
+
+use std::ffi::{OsStr, OsString};
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+/// Candidate names for `file_name`: `name.ext`, then `name-1.ext`, `name-2.ext`, ...
+pub fn candidates(file_name: &OsStr) -> impl Iterator<Item = OsString> + '_ {
+    let path = Path::new(file_name);
+    let stem = path.file_stem().unwrap_or(file_name);
+    let extension = path.extension();
+    (0u32..).map(move |n| {
+        if n == 0 {
+            return file_name.to_os_string();
+        }
+        let mut name = stem.to_os_string();
+        name.push(format!("-{}", n));
+        if let Some(extension) = extension {
+            name.push(".");
+            name.push(extension);
+        }
+        name
+    })
+}
+
+/// Copies to a new file with the source's permissions. The copy is created
+/// owner-only, so a private key is never readable by others while it fills.
+fn copy_new(file_path: &Path, destination: &Path) -> io::Result<()> {
+    let mut source = File::open(file_path)?;
+    let permissions = source.metadata()?.permissions();
+    let mut target = OpenOptions::new().write(true).create_new(true).mode(0o600).open(destination)?;
+    let copied = io::copy(&mut source, &mut target).and_then(|_| target.set_permissions(permissions));
+    if copied.is_err() {
+        let _ = fs::remove_file(destination);
+    }
+    copied.map(|_| ())
+}
+
+/// Places a file into `dir` under the first free candidate for `file_name`;
+/// `place` must fail with `AlreadyExists` rather than replace a file.
+fn place_into(dir: &Path, file_name: &OsStr, place: impl Fn(&Path) -> io::Result<()>) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    for name in candidates(file_name) {
+        let destination = dir.join(name);
+        match place(&destination) {
+            Ok(()) => return Ok(destination),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    unreachable!("candidate names are unbounded")
+}
+
+/// Copies `file_path` into `dir` as `file_name`, numbering the name if it is taken.
+pub fn copy_into(file_path: &Path, dir: &Path, file_name: &OsStr) -> io::Result<PathBuf> {
+    place_into(dir, file_name, |destination| copy_new(file_path, destination))
+}
+
+/// Moves `file_path` into `dir` as `file_name`, numbering the name if it is taken.
+/// Falls back to copying when the destination is on another filesystem.
+pub fn move_into(file_path: &Path, dir: &Path, file_name: &OsStr) -> io::Result<PathBuf> {
+    let destination = place_into(dir, file_name, |destination| {
+        // Linking fails instead of replacing an existing file, unlike rename.
+        fs::hard_link(file_path, destination).or_else(|e| match e.kind() {
+            io::ErrorKind::AlreadyExists => Err(e),
+            _ => copy_new(file_path, destination),
+        })
+    })?;
+    fs::remove_file(file_path)?;
+    Ok(destination)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn mode(path: &Path) -> u32 {
+        fs::metadata(path).unwrap().permissions().mode() & 0o7777
+    }
+
+    #[test]
+    fn copies_and_moves_keep_the_source_mode() {
+        let dir = std::env::temp_dir().join(format!("magicheck-transfer-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for source_mode in [0o600, 0o755] {
+            let source = dir.join(format!("key-{:o}", source_mode));
+            fs::write(&source, b"secret").unwrap();
+            fs::set_permissions(&source, fs::Permissions::from_mode(source_mode)).unwrap();
+
+            let copied = copy_into(&source, &dir.join("copied"), source.file_name().unwrap()).unwrap();
+            assert_eq!(mode(&copied), source_mode);
+            let moved = move_into(&source, &dir.join("moved"), source.file_name().unwrap()).unwrap();
+            assert_eq!(mode(&moved), source_mode);
+            // Exercises the cross-filesystem fallback directly
+            let fallback = dir.join("fallback");
+            copy_new(&moved, &fallback).unwrap();
+            assert_eq!(mode(&fallback), source_mode);
+            fs::remove_file(&fallback).unwrap();
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
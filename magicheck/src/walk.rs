@@ -0,0 +1,47 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Expands `paths` into the files to examine.
+///
+/// Directories are descended into when `recursive` is set; symbolic links found
+/// while descending are skipped so link loops cannot trap the walk. Paths given
+/// explicitly are always kept, so errors on them surface when they are read.
+/// Errors hit while listing directories are passed to `on_error`.
+pub fn collect_files(paths: &[String], recursive: bool, on_error: &mut dyn FnMut(&Path, io::Error)) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for path in paths {
+        let path = PathBuf::from(path);
+        if recursive && path.is_dir() {
+            walk_dir(&path, &mut files, on_error);
+        } else {
+            files.push(path);
+        }
+    }
+    files
+}
+
+fn walk_dir(dir: &Path, files: &mut Vec<PathBuf>, on_error: &mut dyn FnMut(&Path, io::Error)) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => return on_error(dir, e),
+    };
+
+    let mut paths: Vec<PathBuf> = Vec::new();
+    for entry in entries {
+        match entry {
+            Ok(entry) => paths.push(entry.path()),
+            Err(e) => on_error(dir, e),
+        }
+    }
+    paths.sort();
+
+    for path in paths {
+        match fs::symlink_metadata(&path) {
+            Ok(metadata) if metadata.is_dir() => walk_dir(&path, files, on_error),
+            Ok(metadata) if metadata.is_file() => files.push(path),
+            Ok(_) => {}
+            Err(e) => on_error(&path, e),
+        }
+    }
+}
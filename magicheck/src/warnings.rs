@@ -0,0 +1,60 @@
+//! Security warnings: files that are valid as more than one format, and
+//! files that appear cut short.
+
+use crate::signatures::{self, Signature, TRAILERS};
+use crate::to_hex_string;
+
+/// PDF readers accept the `%PDF-` header anywhere in the first kilobyte.
+const PDF_HEADER_WINDOW: usize = 1024;
+/// Head magics shorter than this match by chance too often to flag a polyglot.
+const MIN_POLYGLOT_MAGIC: usize = 4;
+/// What each trailer marks, by MIME type, for truncation warnings.
+const END_MARKERS: &[(&str, &str)] = &[
+    ("image/png", "IEND chunk"),
+    ("image/jpeg", "EOI marker"),
+    ("application/zip", "end of central directory record"),
+    ("application/pdf", "%%EOF marker"),
+    ("application/vnd.apache.parquet", "PAR1 footer"),
+];
+
+fn polyglot_warnings(head: &[u8], signature: &Signature, trailer: Option<&Signature>) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let mut seen = vec![signature.mime];
+    let matched = signature.offset..signature.offset + signature.magic.len();
+    for other in signatures::all() {
+        // A magic within the bytes already matched is a reading of the same header, not a second format
+        let overlaps = other.offset < matched.end && matched.start < other.offset + other.magic.len();
+        if other.magic.len() >= MIN_POLYGLOT_MAGIC && !overlaps && !seen.contains(&other.mime) && other.matches(head) {
+            seen.push(other.mime);
+            warnings.push(format!("polyglot: also a valid {} (magic at offset {})", other.name, other.offset));
+        }
+    }
+    if !seen.contains(&"application/pdf")
+        && let Some(offset) = head[..head.len().min(PDF_HEADER_WINDOW)].windows(5).position(|bytes| bytes == b"%PDF-")
+    {
+        warnings.push(format!("polyglot: also a valid PDF document (header at offset {})", offset));
+    }
+    if let Some(trailer) = trailer.filter(|trailer| trailer.mime != signature.mime) {
+        warnings.push(format!("polyglot: also a valid {}, read from the end of the file", trailer.name));
+    }
+    warnings
+}
+
+/// Warnings for a file whose head matched `signature` and whose end matched
+/// `trailer`. `tail` is `None` when the end of the file could not be read,
+/// in which case truncation is not judged.
+pub fn check(head: &[u8], tail: Option<&[u8]>, signature: Option<&Signature>, trailer: Option<&Signature>) -> Vec<String> {
+    let Some(signature) = signature else {
+        return Vec::new();
+    };
+    let mut warnings = polyglot_warnings(head, signature, trailer);
+    let expected = TRAILERS.iter().find(|expected| expected.mime == signature.mime);
+    if let (Some(_), None, Some(expected)) = (tail, trailer, expected) {
+        let marker = END_MARKERS
+            .iter()
+            .find(|(mime, _)| *mime == expected.mime)
+            .map_or_else(|| format!("{} trailer", to_hex_string(expected.magic)), |(_, marker)| marker.to_string());
+        warnings.push(format!("truncated: {} has no {} at its end", signature.name, marker));
+    }
+    warnings
+}
@@ -0,0 +1,91 @@
+//! `magicheck watch`: classifies files as they land in a directory.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use inotify::{EventMask, Inotify, WatchMask};
+use serde_json::{json, Map, Value};
+
+use crate::cli::WatchOptions;
+use crate::signatures::{Signature, UNKNOWN_MIME};
+use crate::{identify_file, special, transfer};
+
+/// Subdirectory for files of an unknown type or one without a usual extension.
+const UNKNOWN_DIR: &str = "unknown";
+
+/// Per-type subdirectory name, after the type's usual extension.
+fn type_dir(signature: Option<&Signature>) -> &'static str {
+    signature.and_then(|s| s.extensions.first().copied()).unwrap_or(UNKNOWN_DIR)
+}
+
+/// Classifies one landed file, moves it if asked, and describes it as a JSON event.
+fn handle(event: &str, file_path: &Path, options: &WatchOptions) -> io::Result<Value> {
+    if let Some(kind) = special::classify(&fs::metadata(file_path)?, false) {
+        return Err(io::Error::other(format!("not a regular file: {}", kind.label())));
+    }
+    let detection = identify_file(file_path)?;
+    let signature = detection.signature;
+
+    let mut record = Map::new();
+    record.insert("event".into(), json!(event));
+    record.insert("path".into(), json!(file_path.to_string_lossy()));
+    record.insert("type".into(), json!(signature.map(|s| s.name)));
+    record.insert("mime".into(), json!(signature.map_or(UNKNOWN_MIME, |s| s.mime)));
+    record.insert("trailer".into(), json!(detection.trailer.map(|s| s.name)));
+    record.insert("warnings".into(), json!(detection.warnings));
+
+    let allowed = options.allow.is_empty()
+        || signature.is_some_and(|s| options.allow.iter().any(|t| s.is_type(t)));
+    if !options.allow.is_empty() {
+        record.insert("allowed".into(), json!(allowed));
+    }
+
+    let target = match (&options.quarantine, &options.sort) {
+        (Some(quarantine), _) if !allowed => Some(("quarantined", PathBuf::from(quarantine))),
+        (_, Some(sort)) => Some(("sorted", Path::new(sort).join(type_dir(signature)))),
+        _ => None,
+    };
+    match target {
+        Some((action, dir)) => {
+            let file_name = file_path.file_name().unwrap_or(file_path.as_os_str());
+            let destination = transfer::move_into(file_path, &dir, file_name)?;
+            record.insert("action".into(), json!(action));
+            record.insert("destination".into(), json!(destination.to_string_lossy()));
+        }
+        None => {
+            record.insert("action".into(), json!("none"));
+        }
+    }
+    Ok(Value::Object(record))
+}
+
+/// Watches `options.dir` until an error occurs, printing one JSON line per
+/// file that is closed after writing or moved in.
+pub fn run(options: &WatchOptions) -> io::Result<()> {
+    let dir = Path::new(&options.dir);
+    let mut inotify = Inotify::init()?;
+    inotify.watches().add(dir, WatchMask::CLOSE_WRITE | WatchMask::MOVED_TO)?;
+
+    let mut buffer = [0; 4096];
+    loop {
+        for event in inotify.read_events_blocking(&mut buffer)? {
+            if event.mask.contains(EventMask::Q_OVERFLOW) {
+                eprintln!("Error watching '{}': event queue overflowed, some files were missed", dir.display());
+                continue;
+            }
+            let Some(name) = event.name else {
+                continue;
+            };
+            if event.mask.contains(EventMask::ISDIR) {
+                continue;
+            }
+            let kind = if event.mask.contains(EventMask::MOVED_TO) { "moved_to" } else { "close_write" };
+            let file_path = dir.join(name);
+            match handle(kind, &file_path, options) {
+                Ok(record) => println!("{}", record),
+                Err(e) => eprintln!("Error processing file '{}': {}", file_path.display(), e),
+            }
+        }
+    }
+}
@@ -0,0 +1,22 @@
+//! ZIP structure checks.
+
+use crate::bytes::u16_le;
+
+/// Size of the end of central directory record, not counting its comment.
+pub const END_RECORD_SIZE: usize = 22;
+const END_RECORD_MAGIC: &[u8] = b"PK\x05\x06";
+
+/// Position in `tail` of an end of central directory record whose comment
+/// runs exactly to the end of the file.
+pub fn end_record(tail: &[u8]) -> Option<usize> {
+    (0..=tail.len().checked_sub(END_RECORD_SIZE)?).rev().find(|&position| {
+        tail[position..].starts_with(END_RECORD_MAGIC)
+            && u16_le(tail, position + 20)
+                .is_some_and(|comment| position + END_RECORD_SIZE + comment as usize == tail.len())
+    })
+}
+
+/// Whether `tail` ends with a ZIP end of central directory record.
+pub fn ends_with_end_record(tail: &[u8]) -> bool {
+    end_record(tail).is_some()
+}